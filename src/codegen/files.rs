@@ -0,0 +1,406 @@
+//! In-memory generated output
+//!
+//! Generators render into a [`GeneratedFiles`] set first, which can then be
+//! written to disk or compared against what is already there. When a manifest
+//! is attached, writing also prunes files from previous runs that are no
+//! longer generated and refuses to overwrite files sqlift did not create.
+
+use std::collections::BTreeMap;
+use std::fs;
+use std::io::ErrorKind;
+use std::path::{Path, PathBuf};
+
+use similar::TextDiff;
+use tracing::{debug, info, warn};
+
+use crate::codegen::manifest::Manifest;
+use crate::error::SqliftError;
+
+/// A set of rendered files keyed by path relative to an output root
+#[derive(Debug, Clone, Default)]
+pub struct GeneratedFiles {
+    root: PathBuf,
+    files: BTreeMap<PathBuf, String>,
+    /// Where to record generated files, relative to the root
+    manifest: Option<PathBuf>,
+}
+
+/// A generated file whose contents differ from the file on disk
+#[derive(Debug, Clone)]
+pub struct StaleFile {
+    /// Path relative to the output root
+    pub path: PathBuf,
+    /// Unified diff from the file on disk to the generated contents
+    pub diff: String,
+}
+
+impl GeneratedFiles {
+    pub fn new(root: PathBuf) -> Self {
+        Self {
+            root,
+            files: BTreeMap::new(),
+            manifest: None,
+        }
+    }
+
+    /// Track written files in a manifest at the given path (relative to the root)
+    pub fn with_manifest(mut self, path: impl Into<PathBuf>) -> Self {
+        self.manifest = Some(path.into());
+        self
+    }
+
+    /// Directory the relative paths are resolved against
+    pub fn root(&self) -> &Path {
+        &self.root
+    }
+
+    /// Add a file, replacing any previous contents at the same path
+    pub fn insert(&mut self, path: impl Into<PathBuf>, contents: String) {
+        self.files.insert(path.into(), contents);
+    }
+
+    pub fn get(&self, path: impl AsRef<Path>) -> Option<&str> {
+        self.files.get(path.as_ref()).map(String::as_str)
+    }
+
+    /// Iterate over files in path order
+    pub fn iter(&self) -> impl Iterator<Item = (&Path, &str)> {
+        self.files
+            .iter()
+            .map(|(path, contents)| (path.as_path(), contents.as_str()))
+    }
+
+    pub fn len(&self) -> usize {
+        self.files.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.files.is_empty()
+    }
+
+    /// Write every file under the root, creating directories as needed
+    ///
+    /// With a manifest attached, existing files that sqlift did not generate
+    /// are only overwritten when `force` is set, and files recorded by the
+    /// previous run that are no longer generated are deleted.
+    pub fn write(&self, force: bool) -> Result<(), SqliftError> {
+        let previous = self.load_manifest()?;
+
+        if let Some(previous) = &previous {
+            self.check_overwrites(previous, force)?;
+        }
+
+        for (relative, contents) in &self.files {
+            let path = self.root.join(relative);
+            if let Some(parent) = path.parent() {
+                if !parent.as_os_str().is_empty() {
+                    fs::create_dir_all(parent)?;
+                }
+            }
+            fs::write(&path, contents)?;
+            debug!(path = ?path, "Wrote generated file");
+        }
+
+        if let Some(manifest_path) = &self.manifest {
+            if let Some(previous) = &previous {
+                self.prune(previous, force)?;
+            }
+
+            fs::write(self.root.join(manifest_path), self.manifest().to_json())?;
+            debug!(path = ?manifest_path, "Wrote manifest");
+        }
+
+        Ok(())
+    }
+
+    /// Compare against the files on disk and return those that are missing or differ
+    ///
+    /// Files recorded in the manifest that would be pruned are reported too.
+    pub fn check(&self) -> Result<Vec<StaleFile>, SqliftError> {
+        let mut stale = Vec::new();
+
+        for (relative, contents) in &self.files {
+            let path = self.root.join(relative);
+            let (existing, old_header) = match fs::read_to_string(&path) {
+                Ok(existing) => (existing, format!("a/{}", relative.display())),
+                Err(e) if e.kind() == ErrorKind::NotFound => {
+                    (String::new(), "/dev/null".to_string())
+                }
+                Err(e) => return Err(e.into()),
+            };
+
+            if &existing == contents {
+                debug!(path = ?path, "Generated file is up to date");
+                continue;
+            }
+
+            let diff = TextDiff::from_lines(&existing, contents)
+                .unified_diff()
+                .header(&old_header, &format!("b/{}", relative.display()))
+                .to_string();
+
+            stale.push(StaleFile {
+                path: relative.clone(),
+                diff,
+            });
+        }
+
+        if let Some(previous) = self.load_manifest()? {
+            for relative in self.removed_files(&previous) {
+                let Ok(existing) = fs::read_to_string(self.root.join(relative)) else {
+                    continue;
+                };
+
+                let diff = TextDiff::from_lines(existing.as_str(), "")
+                    .unified_diff()
+                    .header(&format!("a/{}", relative.display()), "/dev/null")
+                    .to_string();
+
+                stale.push(StaleFile {
+                    path: relative.clone(),
+                    diff,
+                });
+            }
+        }
+
+        Ok(stale)
+    }
+
+    /// Build the manifest describing this file set
+    pub fn manifest(&self) -> Manifest {
+        let mut manifest = Manifest::default();
+        for (relative, contents) in &self.files {
+            manifest.insert(relative.clone(), contents);
+        }
+        manifest
+    }
+
+    fn load_manifest(&self) -> Result<Option<Manifest>, SqliftError> {
+        match &self.manifest {
+            Some(path) => {
+                let path = self.root.join(path);
+                let manifest = Manifest::load(&path)?;
+                if manifest.is_none() && path.parent().is_some_and(Path::exists) {
+                    debug!(path = ?path, "No manifest found, treating output as unmanaged");
+                    return Ok(Some(Manifest::default()));
+                }
+                Ok(manifest)
+            }
+            None => Ok(None),
+        }
+    }
+
+    /// Files recorded by a previous run that this set no longer contains
+    fn removed_files<'a>(&self, previous: &'a Manifest) -> Vec<&'a PathBuf> {
+        previous
+            .files
+            .keys()
+            .filter(|path| !self.files.contains_key(*path))
+            .collect()
+    }
+
+    /// Refuse to overwrite existing files that were not generated by sqlift
+    fn check_overwrites(&self, previous: &Manifest, force: bool) -> Result<(), SqliftError> {
+        for (relative, contents) in &self.files {
+            let path = self.root.join(relative);
+            let existing = match fs::read_to_string(&path) {
+                Ok(existing) => existing,
+                Err(e) if e.kind() == ErrorKind::NotFound => continue,
+                Err(e) => return Err(e.into()),
+            };
+
+            if &existing == contents {
+                continue;
+            }
+
+            if !previous.files.contains_key(relative) {
+                if !force {
+                    return Err(SqliftError::UnmanagedFile(path.display().to_string()));
+                }
+                warn!(path = ?path, "Overwriting file not generated by sqlift");
+            } else if !previous.matches(relative, &existing) {
+                warn!(path = ?path, "Overwriting generated file that was modified by hand");
+            }
+        }
+        Ok(())
+    }
+
+    /// Delete files from the previous run that are no longer generated
+    ///
+    /// Files modified since they were generated are kept unless `force` is set.
+    fn prune(&self, previous: &Manifest, force: bool) -> Result<(), SqliftError> {
+        for relative in self.removed_files(previous) {
+            let path = self.root.join(relative);
+            let existing = match fs::read_to_string(&path) {
+                Ok(existing) => existing,
+                Err(e) if e.kind() == ErrorKind::NotFound => continue,
+                Err(e) => return Err(e.into()),
+            };
+
+            if !force && !previous.matches(relative, &existing) {
+                warn!(path = ?path, "Stale generated file was modified by hand, not removing");
+                continue;
+            }
+
+            fs::remove_file(&path)?;
+            info!(path = ?path, "Removed stale generated file");
+        }
+        Ok(())
+    }
+}
+
+/// Split an output path into the root directory and its final component
+///
+/// Generated paths are relative to the parent of the configured output so
+/// that files next to the package (or flat module) can share one root.
+pub(crate) fn split_output_path(path: &Path) -> (PathBuf, PathBuf) {
+    match (path.parent(), path.file_name()) {
+        (Some(parent), Some(name)) => {
+            let root = if parent.as_os_str().is_empty() {
+                PathBuf::from(".")
+            } else {
+                parent.to_path_buf()
+            };
+            (root, PathBuf::from(name))
+        }
+        _ => (path.to_path_buf(), PathBuf::new()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn temp_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("sqlift-{}-{}", name, std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        dir
+    }
+
+    #[test]
+    fn test_split_output_path() {
+        assert_eq!(
+            split_output_path(Path::new("./database")),
+            (PathBuf::from("."), PathBuf::from("database"))
+        );
+        assert_eq!(
+            split_output_path(Path::new("database")),
+            (PathBuf::from("."), PathBuf::from("database"))
+        );
+        assert_eq!(
+            split_output_path(Path::new("src/app/db.py")),
+            (PathBuf::from("src/app"), PathBuf::from("db.py"))
+        );
+    }
+
+    #[test]
+    fn test_write_and_check() {
+        let root = temp_dir("files");
+        let mut files = GeneratedFiles::new(root.clone());
+        files.insert("pkg/a.py", "a = 1\n".to_string());
+        files.insert("pkg/b.py", "b = 2\n".to_string());
+
+        // Nothing on disk yet: everything is stale
+        let stale = files.check().unwrap();
+        assert_eq!(stale.len(), 2);
+        assert!(stale[0].diff.contains("--- /dev/null"));
+
+        files.write(false).unwrap();
+        assert!(files.check().unwrap().is_empty());
+
+        fs::write(root.join("pkg/b.py"), "b = 3\n").unwrap();
+        let stale = files.check().unwrap();
+        assert_eq!(stale.len(), 1);
+        assert_eq!(stale[0].path, PathBuf::from("pkg/b.py"));
+        assert!(stale[0].diff.contains("--- a/pkg/b.py"));
+        assert!(stale[0].diff.contains("-b = 3"));
+        assert!(stale[0].diff.contains("+b = 2"));
+
+        fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[test]
+    fn test_manifest_prunes_removed_files() {
+        let root = temp_dir("prune");
+        let mut files = GeneratedFiles::new(root.clone()).with_manifest("pkg/.manifest.json");
+        files.insert("pkg/users.py", "users\n".to_string());
+        files.insert("pkg/orders.py", "orders\n".to_string());
+        files.write(false).unwrap();
+        assert!(root.join("pkg/.manifest.json").exists());
+
+        // orders is no longer generated and is reported by check, then removed
+        let mut next = GeneratedFiles::new(root.clone()).with_manifest("pkg/.manifest.json");
+        next.insert("pkg/users.py", "users\n".to_string());
+        let stale = next.check().unwrap();
+        assert_eq!(stale.len(), 1);
+        assert_eq!(stale[0].path, PathBuf::from("pkg/orders.py"));
+
+        next.write(false).unwrap();
+        assert!(root.join("pkg/users.py").exists());
+        assert!(!root.join("pkg/orders.py").exists());
+
+        fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[test]
+    fn test_manifest_keeps_modified_stale_files() {
+        let root = temp_dir("modified");
+        let mut files = GeneratedFiles::new(root.clone()).with_manifest("pkg/.manifest.json");
+        files.insert("pkg/orders.py", "orders\n".to_string());
+        files.write(false).unwrap();
+        fs::write(root.join("pkg/orders.py"), "hand edited\n").unwrap();
+
+        let next = GeneratedFiles::new(root.clone()).with_manifest("pkg/.manifest.json");
+        next.write(false).unwrap();
+        assert!(root.join("pkg/orders.py").exists());
+
+        fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[test]
+    fn test_manifest_refuses_unmanaged_files() {
+        let root = temp_dir("unmanaged");
+        fs::create_dir_all(root.join("pkg")).unwrap();
+        fs::write(root.join("pkg/users.py"), "handwritten\n").unwrap();
+
+        let mut files = GeneratedFiles::new(root.clone()).with_manifest("pkg/.manifest.json");
+        files.insert("pkg/users.py", "users\n".to_string());
+
+        let err = files.write(false).unwrap_err();
+        assert!(matches!(err, SqliftError::UnmanagedFile(_)));
+        assert_eq!(
+            fs::read_to_string(root.join("pkg/users.py")).unwrap(),
+            "handwritten\n"
+        );
+
+        files.write(true).unwrap();
+        assert_eq!(
+            fs::read_to_string(root.join("pkg/users.py")).unwrap(),
+            "users\n"
+        );
+
+        fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[test]
+    fn test_manifest_adopts_matching_files() {
+        let root = temp_dir("adopt");
+        fs::create_dir_all(root.join("pkg")).unwrap();
+        fs::write(root.join("pkg/users.py"), "users\n").unwrap();
+        fs::write(root.join("pkg/orders.py"), "old orders\n").unwrap();
+
+        // Without a manifest, only files that differ need --force
+        let mut files = GeneratedFiles::new(root.clone()).with_manifest("pkg/.manifest.json");
+        files.insert("pkg/users.py", "users\n".to_string());
+        files.write(false).unwrap();
+        assert!(root.join("pkg/.manifest.json").exists());
+
+        files.insert("pkg/orders.py", "orders\n".to_string());
+        fs::remove_file(root.join("pkg/.manifest.json")).unwrap();
+        let err = files.write(false).unwrap_err();
+        assert!(err.to_string().contains("pass --force"));
+        files.write(true).unwrap();
+
+        fs::remove_dir_all(&root).unwrap();
+    }
+}
@@ -0,0 +1,84 @@
+//! Generation manifest
+//!
+//! Records which files sqlift generated, with content hashes, so later runs
+//! can prune files that are no longer produced and avoid clobbering files
+//! sqlift did not create.
+
+use std::collections::BTreeMap;
+use std::fs;
+use std::io::ErrorKind;
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+use crate::error::SqliftError;
+
+/// File name of the manifest written into generated packages
+pub const MANIFEST_FILE: &str = ".sqlift-manifest.json";
+
+/// Current manifest format version
+pub const MANIFEST_VERSION: u32 = 1;
+
+/// Generated files and their content hashes, keyed by path relative to the output root
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Manifest {
+    pub version: u32,
+    pub files: BTreeMap<PathBuf, String>,
+}
+
+impl Default for Manifest {
+    fn default() -> Self {
+        Self {
+            version: MANIFEST_VERSION,
+            files: BTreeMap::new(),
+        }
+    }
+}
+
+impl Manifest {
+    /// Record a file and the hash of its contents
+    pub fn insert(&mut self, path: PathBuf, contents: &str) {
+        self.files.insert(path, content_hash(contents));
+    }
+
+    /// Check whether the given contents match the recorded hash for a path
+    pub fn matches(&self, path: &Path, contents: &str) -> bool {
+        self.files
+            .get(path)
+            .is_some_and(|hash| *hash == content_hash(contents))
+    }
+
+    /// Load a manifest, returning `None` if the file does not exist
+    pub fn load(path: &Path) -> Result<Option<Self>, SqliftError> {
+        let contents = match fs::read_to_string(path) {
+            Ok(contents) => contents,
+            Err(e) if e.kind() == ErrorKind::NotFound => return Ok(None),
+            Err(e) => return Err(e.into()),
+        };
+
+        let manifest: Manifest = serde_json::from_str(&contents).map_err(|e| {
+            SqliftError::Config(format!("Invalid manifest {}: {}", path.display(), e))
+        })?;
+
+        if manifest.version != MANIFEST_VERSION {
+            return Err(SqliftError::Config(format!(
+                "Unsupported manifest version {} in {}",
+                manifest.version,
+                path.display()
+            )));
+        }
+
+        Ok(Some(manifest))
+    }
+
+    /// Serialize the manifest as pretty-printed JSON
+    pub fn to_json(&self) -> String {
+        serde_json::to_string_pretty(self).expect("manifest serialization cannot fail") + "\n"
+    }
+}
+
+/// SHA-256 hex digest of file contents
+pub fn content_hash(contents: &str) -> String {
+    format!("{:x}", Sha256::digest(contents.as_bytes()))
+}
@@ -3,13 +3,21 @@
 //! This module provides functionality for generating typed data access code
 //! from the introspected database schema.
 
+use std::collections::BTreeMap;
 use std::path::PathBuf;
 
 use crate::prelude::{Schema, SqliftError};
 
+pub mod files;
+pub mod manifest;
 pub mod python;
 
-pub use python::PythonGenerator;
+pub use files::{GeneratedFiles, StaleFile};
+pub use manifest::{Manifest, MANIFEST_FILE};
+pub use python::{PythonGenerator, SqlAlchemyGenerator};
+
+/// Conventional name of the soft delete timestamp column
+pub const DEFAULT_SOFT_DELETE_COLUMN: &str = "deleted_at";
 
 /// Output mode for generated code
 #[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
@@ -31,6 +39,30 @@ pub enum FunctionStyle {
     Class,
 }
 
+/// Class library used for generated Python records and parameters
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum PythonModels {
+    /// Standard library dataclasses
+    #[default]
+    Dataclass,
+    /// Pydantic v2 models with validation
+    Pydantic,
+    /// attrs classes with length validators
+    Attrs,
+    /// msgspec structs
+    Msgspec,
+}
+
+/// Database driver targeted by generated Python code
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum PythonDriver {
+    /// psycopg 3
+    #[default]
+    Psycopg,
+    /// psycopg2, for code that can't move to psycopg 3
+    Psycopg2,
+}
+
 /// Configuration for code generation
 #[derive(Debug, Clone)]
 pub struct CodeGenConfig {
@@ -40,6 +72,24 @@ pub struct CodeGenConfig {
     pub output_mode: OutputMode,
     /// Function style
     pub function_style: FunctionStyle,
+    /// Python model classes
+    pub python_models: PythonModels,
+    /// Generate immutable records (frozen, slotted dataclasses)
+    pub frozen_records: bool,
+    /// Database driver for generated Python code
+    pub python_driver: PythonDriver,
+    /// Generate pytest smoke tests in a `tests/` package next to the output
+    pub tests: bool,
+    /// Overwrite files that were not generated by sqlift
+    pub force: bool,
+    /// Optimistic locking column used by every table that has it
+    pub version_column: Option<String>,
+    /// Per-table optimistic locking columns, overriding `version_column`
+    pub table_version_columns: BTreeMap<String, String>,
+    /// Soft delete timestamp column used by every table that has it
+    pub soft_delete_column: Option<String>,
+    /// Per-table soft delete columns, overriding `soft_delete_column`
+    pub table_soft_delete_columns: BTreeMap<String, String>,
 }
 
 impl CodeGenConfig {
@@ -48,6 +98,15 @@ impl CodeGenConfig {
             output_path,
             output_mode: OutputMode::default(),
             function_style: FunctionStyle::default(),
+            python_models: PythonModels::default(),
+            frozen_records: false,
+            python_driver: PythonDriver::default(),
+            tests: false,
+            force: false,
+            version_column: None,
+            table_version_columns: BTreeMap::new(),
+            soft_delete_column: None,
+            table_soft_delete_columns: BTreeMap::new(),
         }
     }
 
@@ -60,10 +119,93 @@ impl CodeGenConfig {
         self.function_style = style;
         self
     }
+
+    pub fn with_python_models(mut self, models: PythonModels) -> Self {
+        self.python_models = models;
+        self
+    }
+
+    pub fn with_frozen_records(mut self, frozen: bool) -> Self {
+        self.frozen_records = frozen;
+        self
+    }
+
+    pub fn with_python_driver(mut self, driver: PythonDriver) -> Self {
+        self.python_driver = driver;
+        self
+    }
+
+    pub fn with_tests(mut self, tests: bool) -> Self {
+        self.tests = tests;
+        self
+    }
+
+    pub fn with_force(mut self, force: bool) -> Self {
+        self.force = force;
+        self
+    }
+
+    pub fn with_version_column(mut self, column: impl Into<String>) -> Self {
+        self.version_column = Some(column.into());
+        self
+    }
+
+    pub fn with_table_version_column(
+        mut self,
+        table: impl Into<String>,
+        column: impl Into<String>,
+    ) -> Self {
+        self.table_version_columns
+            .insert(table.into(), column.into());
+        self
+    }
+
+    pub fn with_soft_delete_column(mut self, column: impl Into<String>) -> Self {
+        self.soft_delete_column = Some(column.into());
+        self
+    }
+
+    pub fn with_table_soft_delete_column(
+        mut self,
+        table: impl Into<String>,
+        column: impl Into<String>,
+    ) -> Self {
+        self.table_soft_delete_columns
+            .insert(table.into(), column.into());
+        self
+    }
+
+    /// Optimistic locking column configured for a table, if any
+    pub fn version_column_for(&self, table: &str) -> Option<&str> {
+        self.table_version_columns
+            .get(table)
+            .or(self.version_column.as_ref())
+            .map(String::as_str)
+    }
+
+    /// Soft delete column configured for a table, if any
+    pub fn soft_delete_column_for(&self, table: &str) -> Option<&str> {
+        self.table_soft_delete_columns
+            .get(table)
+            .or(self.soft_delete_column.as_ref())
+            .map(String::as_str)
+    }
 }
 
 /// Trait for language-specific code generators
 pub trait CodeGenerator {
-    /// Generate code for the given schema
-    fn generate(&self, schema: &Schema, config: &CodeGenConfig) -> Result<(), SqliftError>;
+    /// Render code for the given schema into an in-memory set of files
+    ///
+    /// Paths are relative to [`GeneratedFiles::root`], which is derived from
+    /// `config.output_path`. Nothing is written to disk.
+    fn render(
+        &self,
+        schema: &Schema,
+        config: &CodeGenConfig,
+    ) -> Result<GeneratedFiles, SqliftError>;
+
+    /// Generate code for the given schema and write it to the output path
+    fn generate(&self, schema: &Schema, config: &CodeGenConfig) -> Result<(), SqliftError> {
+        self.render(schema, config)?.write(config.force)
+    }
 }
@@ -0,0 +1,218 @@
+//! In-memory fake repositories for generated Python code
+//!
+//! Renders `fakes.py` next to the generated repositories, with a
+//! `Fake<Singular>Repository` per table that keeps rows in a dict so
+//! application tests can run without a database.
+
+use std::collections::BTreeSet;
+
+use super::{collect_imports, driver_name, sample_value, upsert_targets, SampleValue};
+use super::{soft_delete_column, version_column, PythonGenerator, VersionColumn};
+use crate::codegen::{CodeGenConfig, PythonModels};
+use crate::error::SqliftError;
+use crate::schema::Schema;
+
+impl PythonGenerator {
+    /// Render fakes.py with a fake repository per table
+    pub(super) fn render_fakes(
+        &self,
+        schema: &Schema,
+        config: &CodeGenConfig,
+    ) -> Result<String, SqliftError> {
+        let template = self
+            .env
+            .get_template("fakes")
+            .map_err(|e| SqliftError::CodeGen {
+                table: "fakes".to_string(),
+                message: format!("Template error: {}", e),
+            })?;
+
+        // Method signatures annotate with the column types
+        let mut imports = BTreeSet::new();
+        let mut enums = BTreeSet::new();
+        for imp in collect_imports(schema, PythonModels::Dataclass) {
+            match imp.strip_prefix("from .enums import ") {
+                Some(name) => enums.insert(name.to_string()),
+                None if imp == "from typing import Any" => false,
+                None => imports.insert(imp),
+            };
+        }
+        let mut has_version = false;
+        let mut has_unset = false;
+
+        let mut tables_ctx = Vec::new();
+        for table in &schema.tables {
+            let ctx = self.build_table_context(table, schema, config)?;
+            let version = version_column(table, config)?;
+            let soft_delete = soft_delete_column(table, config)?;
+            let has_pk = !table.primary_key.is_empty();
+            let has_update = has_pk && !table.non_pk_columns().is_empty();
+            let targets = upsert_targets(table);
+            has_version |= version.is_some();
+            has_unset |= has_update;
+
+            let insert_columns = table.insert_columns();
+            let defaults: Vec<_> = table
+                .columns
+                .iter()
+                .filter(|col| !insert_columns.iter().any(|c| c.name == col.name))
+                .map(|col| {
+                    let value = match &version {
+                        Some(VersionColumn::Integer(name)) if *name == col.name => "1".to_string(),
+                        _ => sample_value(
+                            &col.data_type,
+                            SampleValue::Default,
+                            schema,
+                            &mut imports,
+                            &mut enums,
+                        ),
+                    };
+                    minijinja::context! { name => &col.name, value => value }
+                })
+                .collect();
+
+            let soft_delete_now = soft_delete.and_then(|name| {
+                let col = table.columns.iter().find(|col| col.name == name)?;
+                Some(sample_value(
+                    &col.data_type,
+                    SampleValue::Default,
+                    schema,
+                    &mut imports,
+                    &mut enums,
+                ))
+            });
+
+            let mut unique = Vec::new();
+            if has_pk {
+                unique.push(table.primary_key.clone());
+            }
+            unique.extend(table.unique_constraints.iter().map(|c| c.columns.clone()));
+
+            let class_name = table.singular_class_name();
+            let mut module_imports = vec![
+                format!("{}Filter", class_name),
+                format!("{}Record", class_name),
+            ];
+            if !insert_columns.is_empty() {
+                module_imports.push(format!("{}InsertParams", class_name));
+            }
+            if has_update {
+                module_imports.push(format!("{}UpdateParams", class_name));
+            }
+            if !targets.is_empty() {
+                module_imports.push(format!("{}UpsertParams", class_name));
+            }
+            module_imports.sort();
+
+            tables_ctx.push(minijinja::context! {
+                module_imports => module_imports,
+                not_null => table
+                    .columns
+                    .iter()
+                    .filter(|col| !col.is_nullable)
+                    .map(|col| &col.name)
+                    .collect::<Vec<_>>(),
+                unique => unique,
+                defaults => defaults,
+                soft_delete_now => soft_delete_now,
+                ..ctx
+            });
+        }
+
+        let ctx = minijinja::context! {
+            driver => driver_name(config.python_driver),
+            imports => imports,
+            enums => enums,
+            has_pk => schema.tables.iter().any(|t| !t.primary_key.is_empty()),
+            has_version => has_version,
+            has_unset => has_unset,
+            tables => tables_ctx,
+        };
+
+        template.render(ctx).map_err(|e| SqliftError::CodeGen {
+            table: "fakes".to_string(),
+            message: format!("Render error: {}", e),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::codegen::{CodeGenerator, FunctionStyle};
+    use crate::schema::{Column, DataType, EnumType, Table, UniqueConstraint};
+    use std::path::PathBuf;
+
+    fn column(name: &str, data_type: DataType, is_nullable: bool) -> Column {
+        Column {
+            name: name.to_string(),
+            data_type,
+            is_nullable,
+            has_default: false,
+            is_auto_generated: false,
+        }
+    }
+
+    fn schema() -> Schema {
+        Schema {
+            name: "public".to_string(),
+            tables: vec![Table {
+                name: "users".to_string(),
+                columns: vec![
+                    Column {
+                        has_default: true,
+                        is_auto_generated: true,
+                        ..column("id", DataType::Integer, false)
+                    },
+                    column("email", DataType::Varchar(Some(255)), false),
+                    Column {
+                        has_default: true,
+                        ..column("role", DataType::Enum("user_role".to_string()), false)
+                    },
+                    Column {
+                        has_default: true,
+                        ..column("created_at", DataType::TimestampTz, false)
+                    },
+                    column("deleted_at", DataType::TimestampTz, true),
+                ],
+                primary_key: vec!["id".to_string()],
+                unique_constraints: vec![UniqueConstraint {
+                    name: "users_email_key".to_string(),
+                    columns: vec!["email".to_string()],
+                }],
+                foreign_keys: vec![],
+            }],
+            enums: vec![EnumType {
+                name: "user_role".to_string(),
+                values: vec!["member".to_string(), "admin".to_string()],
+            }],
+        }
+    }
+
+    #[test]
+    fn test_render_fakes() {
+        let config = CodeGenConfig::new(PathBuf::from("out/database"))
+            .with_function_style(FunctionStyle::Class)
+            .with_soft_delete_column("deleted_at");
+        let files = PythonGenerator::new().render(&schema(), &config).unwrap();
+
+        let fakes = files.get("database/fakes.py").unwrap();
+        assert!(fakes.contains("class FakeUserRepository(_FakeRepository):"));
+        assert!(fakes.contains("    _unique = ((\"id\",), (\"email\",))\n"));
+        assert!(fakes.contains("            \"id\": next(self._sequence),\n"));
+        assert!(fakes.contains("            \"role\": UserRole.MEMBER,\n"));
+        assert!(fakes.contains("from psycopg.errors import NotNullViolation, UniqueViolation"));
+        // Same signature as UserRepository.update
+        assert!(fakes.contains("        email: str | _Unset = UNSET,\n"));
+        assert!(fakes.contains("self._update((id,), {\"deleted_at\": datetime.now(timezone.utc)})"));
+        // Like the generated SQL, upserts reject NULL conflict keys
+        assert!(fakes.contains(
+            "                if None in conflict_key:\n                    raise ValueError(f\"Conflict columns can't be NULL for upsert: {conflict_key!r}\")\n"
+        ));
+
+        // Standalone functions have no repositories to fake
+        let config = config.with_function_style(FunctionStyle::Standalone);
+        let files = PythonGenerator::new().render(&schema(), &config).unwrap();
+        assert!(files.get("database/fakes.py").is_none());
+    }
+}
@@ -2,16 +2,26 @@
 //!
 //! Generates typed Python data access code.
 
-use std::collections::HashSet;
-use std::fs;
+use std::collections::{BTreeSet, HashSet};
+use std::path::Path;
 
 use minijinja::Environment;
-use tracing::{debug, info};
+use tracing::{debug, info, warn};
 
-use crate::codegen::{CodeGenConfig, CodeGenerator, FunctionStyle, OutputMode};
+use crate::codegen::files::split_output_path;
+use crate::codegen::{
+    CodeGenConfig, CodeGenerator, FunctionStyle, GeneratedFiles, OutputMode, PythonDriver,
+    PythonModels, MANIFEST_FILE,
+};
 use crate::error::SqliftError;
 use crate::schema::{to_pascal_case, Column, DataType, EnumType, Schema, Table};
 
+mod fakes;
+mod pytest;
+mod sqlalchemy;
+
+pub use sqlalchemy::SqlAlchemyGenerator;
+
 /// Python code generator
 pub struct PythonGenerator {
     env: Environment<'static>,
@@ -22,12 +32,22 @@ impl PythonGenerator {
         let mut env = Environment::new();
 
         // Register templates
-        env.add_template("record", include_str!("templates/record.py.jinja"))
-            .expect("Failed to load python record template");
+        env.add_template("models", include_str!("templates/models.py.jinja"))
+            .expect("Failed to load models template");
         env.add_template("standalone", include_str!("templates/standalone.py.jinja"))
             .expect("Failed to load standalone template");
         env.add_template("repository", include_str!("templates/repository.py.jinja"))
             .expect("Failed to load repository template");
+        env.add_template(
+            "bulk_insert",
+            include_str!("templates/bulk_insert.py.jinja"),
+        )
+        .expect("Failed to load bulk insert template");
+        env.add_template(
+            "bulk_upsert",
+            include_str!("templates/bulk_upsert.py.jinja"),
+        )
+        .expect("Failed to load bulk upsert template");
         env.add_template("init", include_str!("templates/init.py.jinja"))
             .expect("Failed to load init template");
         env.add_template("flat", include_str!("templates/flat.py.jinja"))
@@ -36,6 +56,37 @@ impl PythonGenerator {
             .expect("Failed to load enum template");
         env.add_template("types", include_str!("templates/types.py.jinja"))
             .expect("Failed to load types template");
+        env.add_template(
+            "connection_setup",
+            include_str!("templates/connection_setup.py.jinja"),
+        )
+        .expect("Failed to load connection setup template");
+        env.add_template("connection", include_str!("templates/connection.py.jinja"))
+            .expect("Failed to load connection template");
+        env.add_template(
+            "database_class",
+            include_str!("templates/database_class.py.jinja"),
+        )
+        .expect("Failed to load database class template");
+        env.add_template("database", include_str!("templates/database.py.jinja"))
+            .expect("Failed to load database template");
+        env.add_template("fakes", include_str!("templates/fakes.py.jinja"))
+            .expect("Failed to load fakes template");
+        env.add_template(
+            "tests_conftest",
+            include_str!("templates/tests/conftest.py.jinja"),
+        )
+        .expect("Failed to load tests conftest template");
+        env.add_template(
+            "tests_factories",
+            include_str!("templates/tests/factories.py.jinja"),
+        )
+        .expect("Failed to load tests factories template");
+        env.add_template(
+            "tests_table",
+            include_str!("templates/tests/table.py.jinja"),
+        )
+        .expect("Failed to load tests table template");
 
         Self { env }
     }
@@ -48,93 +99,133 @@ impl Default for PythonGenerator {
 }
 
 impl CodeGenerator for PythonGenerator {
-    fn generate(&self, schema: &Schema, config: &CodeGenConfig) -> Result<(), SqliftError> {
+    fn render(
+        &self,
+        schema: &Schema,
+        config: &CodeGenConfig,
+    ) -> Result<GeneratedFiles, SqliftError> {
         info!(
             output = ?config.output_path,
-                mode = ?config.output_mode,
-                style = ?config.function_style,
-                "Generating Python code"
+            mode = ?config.output_mode,
+            style = ?config.function_style,
+            "Generating Python code"
         );
 
-        match config.output_mode {
-            OutputMode::Library => self.generate_library(schema, config),
-            OutputMode::Flat => self.generate_flat(schema, config),
-        }
+        let files = match config.output_mode {
+            OutputMode::Library => self.generate_library(schema, config)?,
+            OutputMode::Flat => self.generate_flat(schema, config)?,
+        };
+
+        info!(
+            files = files.len(),
+            tables = schema.tables.len(),
+            enums = schema.enums.len(),
+            "Python code generation complete"
+        );
+
+        Ok(files)
     }
 }
 
 impl PythonGenerator {
     /// Generate library mode output
-    fn generate_library(&self, schema: &Schema, config: &CodeGenConfig) -> Result<(), SqliftError> {
-        let output_dir = &config.output_path;
-
-        // Create output directory
-        // TODO : shouldcheck parent directory exists like in flat
-        fs::create_dir_all(output_dir)?;
-        debug!(path = ?output_dir, "Created output directory");
+    fn generate_library(
+        &self,
+        schema: &Schema,
+        config: &CodeGenConfig,
+    ) -> Result<GeneratedFiles, SqliftError> {
+        let (root, package) = split_output_path(&config.output_path);
+        let mut files = GeneratedFiles::new(root).with_manifest(package.join(MANIFEST_FILE));
 
         // Generate _types.py with shared types
-        let types_code = self.render_types()?;
-        let types_path = output_dir.join("_types.py");
-        fs::write(&types_path, types_code)?;
-        debug!(path = ?types_path, "Generated _types.py");
+        files.insert(package.join("_types.py"), self.render_types(config)?);
+        debug!("Generated _types.py");
 
         // Generate enum file if there are enums
         if !schema.enums.is_empty() {
-            let enum_code = self.render_enums(&schema.enums)?;
-            let enum_path = output_dir.join("enums.py");
-            fs::write(&enum_path, enum_code)?;
-            debug!(path = ?enum_path, "Generated enums file");
+            files.insert(package.join("enums.py"), self.render_enums(&schema.enums)?);
+            debug!("Generated enums file");
         }
 
+        files.insert(
+            package.join("_connection.py"),
+            self.render_connection(schema, config)?,
+        );
+        debug!("Generated _connection.py");
+
+        files.insert(
+            package.join("_database.py"),
+            self.render_database(schema, config)?,
+        );
+        debug!("Generated _database.py");
+
         // Generate one file per table
         for table in &schema.tables {
             let code = self.render_table(table, schema, config)?;
-            let file_path = output_dir.join(format!("{}.py", table.name));
-            fs::write(&file_path, code)?;
-            debug!(table = ?table.name, path = ?file_path, "Generated table file")
+            files.insert(package.join(format!("{}.py", table.name)), code);
+            debug!(table = ?table.name, "Generated table file")
         }
 
-        let init_code = self.render_init(schema)?;
-        let init_path = output_dir.join("__init__.py");
-        fs::write(&init_path, init_code)?;
-        debug!(path = ?init_path, "Generated __init__.py");
-
-        info!(
-            tables = schema.tables.len(),
-            enums = schema.enums.len(),
-            "Python code generation complete"
+        files.insert(
+            package.join("__init__.py"),
+            self.render_init(schema, config)?,
         );
+        debug!("Generated __init__.py");
+
+        if config.function_style == FunctionStyle::Class {
+            files.insert(package.join("fakes.py"), self.render_fakes(schema, config)?);
+            debug!("Generated fakes.py");
+        }
 
-        Ok(())
+        if config.tests {
+            let module = package.to_string_lossy();
+            self.render_tests(schema, config, &module, Path::new("tests"), &mut files)?;
+            debug!("Generated tests");
+        }
+
+        Ok(files)
     }
 
     /// Generate flat mode output (single file)
-    fn generate_flat(&self, schema: &Schema, config: &CodeGenConfig) -> Result<(), SqliftError> {
+    fn generate_flat(
+        &self,
+        schema: &Schema,
+        config: &CodeGenConfig,
+    ) -> Result<GeneratedFiles, SqliftError> {
         let output_path = &config.output_path;
 
-        // Ensure parent directory exists
-        if let Some(parent) = output_path.parent() {
-            if !parent.as_os_str().is_empty() {
-                fs::create_dir_all(parent)?;
-            }
-        }
-
-        let code = self.render_flat(schema, config)?;
-
         let final_path = if output_path.extension().is_some_and(|ext| ext == "py") {
             output_path.clone()
         } else {
             output_path.with_extension("py")
         };
 
-        fs::write(&final_path, code)?;
-        info!(path = ?final_path, "Generated flat Python file");
+        let (root, file_name) = split_output_path(&final_path);
+        let mut files = GeneratedFiles::new(root);
+        files.insert(file_name.clone(), self.render_flat(schema, config)?);
+        debug!(path = ?final_path, "Generated flat Python file");
 
-        Ok(())
+        if config.tests {
+            if config.function_style == FunctionStyle::Class {
+                // Flat repository classes have no methods to test yet
+                warn!("Skipping tests, which flat output only supports for standalone functions");
+            } else {
+                let module = file_name.with_extension("");
+                self.render_tests(
+                    schema,
+                    config,
+                    &module.to_string_lossy(),
+                    Path::new("tests"),
+                    &mut files,
+                )?;
+                debug!("Generated tests");
+            }
+        }
+
+        Ok(files)
     }
 
-    fn render_types(&self) -> Result<String, SqliftError> {
+    fn render_types(&self, config: &CodeGenConfig) -> Result<String, SqliftError> {
         let template = self
             .env
             .get_template("types")
@@ -144,13 +235,74 @@ impl PythonGenerator {
             })?;
 
         template
-            .render(minijinja::context! {})
+            .render(minijinja::context! {
+                models => models_name(config.python_models),
+                driver => driver_name(config.python_driver),
+            })
             .map_err(|e| SqliftError::CodeGen {
                 table: "_types".to_string(),
                 message: format!("Render error: {}", e),
             })
     }
 
+    /// Render _connection.py with configure() and connect()
+    fn render_connection(
+        &self,
+        schema: &Schema,
+        config: &CodeGenConfig,
+    ) -> Result<String, SqliftError> {
+        let template = self
+            .env
+            .get_template("connection")
+            .map_err(|e| SqliftError::CodeGen {
+                table: "_connection".to_string(),
+                message: format!("Template error: {}", e),
+            })?;
+
+        let ctx = minijinja::context! {
+            driver => driver_name(config.python_driver),
+            schema_name => &schema.name,
+            enums => schema.enums.iter().map(|e| {
+                minijinja::context! {
+                    name => to_pascal_case(&e.name),
+                    db_name => &e.name,
+                }
+            }).collect::<Vec<_>>(),
+            json_dumper => json_dumper(schema),
+            untyped_json => untyped_json(schema),
+        };
+
+        template.render(ctx).map_err(|e| SqliftError::CodeGen {
+            table: "_connection".to_string(),
+            message: format!("Render error: {}", e),
+        })
+    }
+
+    /// Render _database.py with the Database facade
+    fn render_database(
+        &self,
+        schema: &Schema,
+        config: &CodeGenConfig,
+    ) -> Result<String, SqliftError> {
+        let template = self
+            .env
+            .get_template("database")
+            .map_err(|e| SqliftError::CodeGen {
+                table: "_database".to_string(),
+                message: format!("Template error: {}", e),
+            })?;
+
+        let ctx = minijinja::context! {
+            driver => driver_name(config.python_driver),
+            tables => database_repositories(schema, config),
+        };
+
+        template.render(ctx).map_err(|e| SqliftError::CodeGen {
+            table: "_database".to_string(),
+            message: format!("Render error: {}", e),
+        })
+    }
+
     /// Render enums file
     fn render_enums(&self, enums: &[EnumType]) -> Result<String, SqliftError> {
         let template = self
@@ -197,7 +349,9 @@ impl PythonGenerator {
                 message: format!("Template error: {}", e),
             })?;
 
-        let ctx = self.build_table_context(table, schema)?;
+        let ctx = self.build_table_context(table, schema, config)?;
+        // Shared macros take the table like the flat template's loop variable
+        let ctx = minijinja::context! { table => ctx.clone(), ..ctx };
 
         template.render(ctx).map_err(|e| SqliftError::CodeGen {
             table: table.name.clone(),
@@ -218,10 +372,31 @@ impl PythonGenerator {
         let tables_ctx: Vec<_> = schema
             .tables
             .iter()
-            .map(|t| self.build_table_context(t, schema))
+            .map(|t| self.build_table_context(t, schema, config))
             .collect::<Result<_, _>>()?;
 
+        // The inlined pagination cursor codec and _json_value need these whatever
+        // the column types
+        let mut imports = collect_imports(schema, config.python_models);
+        for imp in [
+            "from datetime import date",
+            "from datetime import datetime",
+            "from datetime import time",
+            "from decimal import Decimal",
+            "from uuid import UUID",
+        ] {
+            if !imports.iter().any(|existing| existing == imp) {
+                imports.push(imp.to_string());
+            }
+        }
+        imports.extend(model_import(config.python_models).map(String::from));
+        // Enums are defined in the flat file itself
+        imports.retain(|imp| !imp.starts_with("from .enums import"));
+        imports.sort();
+
         let ctx = minijinja::context! {
+            models => models_name(config.python_models),
+            driver => driver_name(config.python_driver),
             enums => schema.enums.iter().map(|e| {
                 minijinja::context! {
                     name => to_pascal_case(&e.name),
@@ -230,7 +405,12 @@ impl PythonGenerator {
                 }
             }).collect::<Vec<_>>(),
             tables => tables_ctx,
-            imports => collect_imports(schema),
+            schema_name => &schema.name,
+            json_dumper => json_dumper(schema),
+            untyped_json => untyped_json(schema),
+            imports => imports,
+            // Flat repositories are stubs, so the facade only holds the connection
+            database_tables => Vec::<minijinja::Value>::new(),
             function_style => match config.function_style {
                 FunctionStyle::Standalone => "standalone",
                 FunctionStyle::Class => "class",
@@ -244,7 +424,7 @@ impl PythonGenerator {
     }
 
     /// Render __init__.py
-    fn render_init(&self, schema: &Schema) -> Result<String, SqliftError> {
+    fn render_init(&self, schema: &Schema, config: &CodeGenConfig) -> Result<String, SqliftError> {
         let template = self
             .env
             .get_template("init")
@@ -253,33 +433,53 @@ impl PythonGenerator {
                 message: format!("Template error: {}", e),
             })?;
 
-        let ctx = minijinja::context! {
-                tables => schema.tables.iter().map(|t| {
-            let has_pk = !t.primary_key.is_empty();
-            let has_insert_params = !t.insert_columns().is_empty();
-            let has_update_params = has_pk && !t.non_pk_columns().is_empty();
-            let has_upsert = has_pk && !t.has_auto_generated_pk();
-
-            // Build the get_by function name suffix (e.g., "id" or "user_id_and_role_id")
-            let pk_suffix = t.primary_key.join("_and_");
+        let tables_ctx = schema
+            .tables
+            .iter()
+            .map(|t| {
+                let has_pk = !t.primary_key.is_empty();
+                let has_insert_params = !t.insert_columns().is_empty();
+                let has_update_params = has_pk && !t.non_pk_columns().is_empty();
+                let has_upsert = has_pk && !t.has_auto_generated_pk();
+                let bulk_upsert_suffixes: Vec<_> = upsert_targets(t)
+                    .into_iter()
+                    .map(|(suffix, _)| suffix)
+                    .collect();
+
+                // Build the get_by function name suffix (e.g., "id" or "user_id_and_role_id")
+                let pk_suffix = t.primary_key.join("_and_");
+
+                Ok(minijinja::context! {
+                    module_name => &t.name,
+                    table_name => &t.name,
+                    singular_name => t.singular_name(),
+                    record_name => format!("{}Record", t.singular_class_name()),
+                    filter_name => format!("{}Filter", t.singular_class_name()),
+                    insert_params_name => format!("{}InsertParams", t.singular_class_name()),
+                    update_params_name => format!("{}UpdateParams", t.singular_class_name()),
+                    upsert_params_name => format!("{}UpsertParams", t.singular_class_name()),
+                    repository_name => format!("{}Repository", t.singular_class_name()),
+                    has_pk => has_pk,
+                    has_insert_params => has_insert_params,
+                    has_update_params => has_update_params,
+                    has_upsert => has_upsert,
+                    has_bulk_upsert => !bulk_upsert_suffixes.is_empty(),
+                    has_soft_delete => has_pk && soft_delete_column(t, config)?.is_some(),
+                    bulk_upsert_suffixes => bulk_upsert_suffixes,
+                    pk_suffix => pk_suffix,
+                })
+            })
+            .collect::<Result<Vec<_>, SqliftError>>()?;
 
-            minijinja::context! {
-                module_name => &t.name,
-                table_name => &t.name,
-                singular_name => t.singular_name(),
-                record_name => format!("{}Record", t.singular_class_name()),
-                insert_params_name => format!("{}InsertParams", t.singular_class_name()),
-                update_params_name => format!("{}UpdateParams", t.singular_class_name()),
-                has_pk => has_pk,
-                has_insert_params => has_insert_params,
-                has_update_params => has_update_params,
-                has_upsert => has_upsert,
-                pk_suffix => pk_suffix,
-            }
-        }).collect::<Vec<_>>(),
-        has_enums => !schema.enums.is_empty(),
-        enums => schema.enums.iter().map(|e| to_pascal_case(&e.name)).collect::<Vec<_>>(),
-            };
+        let ctx = minijinja::context! {
+            function_style => match config.function_style {
+                FunctionStyle::Standalone => "standalone",
+                FunctionStyle::Class => "class",
+            },
+            tables => tables_ctx,
+            has_enums => !schema.enums.is_empty(),
+            enums => schema.enums.iter().map(|e| to_pascal_case(&e.name)).collect::<Vec<_>>(),
+        };
 
         template.render(ctx).map_err(|e| SqliftError::CodeGen {
             table: "__init__".to_string(),
@@ -292,7 +492,11 @@ impl PythonGenerator {
         &self,
         table: &Table,
         schema: &Schema,
+        config: &CodeGenConfig,
     ) -> Result<minijinja::Value, SqliftError> {
+        let version = version_column(table, config)?;
+        let soft_delete = soft_delete_column(table, config)?;
+
         let columns_ctx: Vec<_> = table
             .columns
             .iter()
@@ -317,23 +521,384 @@ impl PythonGenerator {
             .map(|col| build_column_context(col, schema))
             .collect();
 
-        Ok(minijinja::context! {
+        // The version column is bumped by every write, never set by the caller
+        let is_version = |col: &Column| version == Some(VersionColumn::Integer(&col.name));
+        let update_columns_ctx: Vec<_> = table
+            .non_pk_columns()
+            .into_iter()
+            .filter(|col| !is_version(col))
+            .map(|col| build_column_context(col, schema))
+            .collect();
+
+        let mut select_list = select_list(table);
+        if matches!(version, Some(VersionColumn::Xmin)) {
+            select_list.push_str(", xmin::text::bigint AS xmin");
+        }
+
+        // Primary key columns come first in upsert params since they are
+        // required whenever the primary key is a conflict target
+        let targets = upsert_targets(table);
+        let pk_is_target = targets.iter().any(|(suffix, _)| suffix.is_empty());
+        let (upsert_key_columns, upsert_value_columns): (Vec<&Column>, Vec<&Column>) = table
+            .upsert_columns()
+            .into_iter()
+            .filter(|col| !is_version(col))
+            .partition(|col| pk_is_target && table.primary_key.contains(&col.name));
+
+        let mut imports = collect_table_imports(table, schema, config.python_models);
+        imports.extend(model_import(config.python_models).map(String::from));
+        imports.sort();
+
+        let ctx = minijinja::context! {
+            models => models_name(config.python_models),
+            driver => driver_name(config.python_driver),
+            frozen_records => config.frozen_records,
             table_name => &table.name,
             singular_name => table.singular_name(),
             singular_class_name => table.singular_class_name(),
             record_name => format!("{}Record", table.singular_class_name()),
             class_name => table.singular_class_name(),
             columns => columns_ctx,
+            select_list => select_list,
+            nullable_columns => table
+                .columns
+                .iter()
+                .filter(|col| col.is_nullable)
+                .map(|col| col.name.as_str())
+                .collect::<Vec<_>>(),
             pk_columns => pk_columns_ctx,
             insert_columns => insert_columns_ctx,
             non_pk_columns => non_pk_columns_ctx,
+            update_columns => update_columns_ctx,
+            version => version.as_ref().map(|v| v.context()),
+            soft_delete => soft_delete,
+            upsert_key_columns => upsert_key_columns
+                .iter()
+                .map(|col| build_column_context(col, schema))
+                .collect::<Vec<_>>(),
+            upsert_value_columns => upsert_value_columns
+                .iter()
+                .map(|col| build_column_context(col, schema))
+                .collect::<Vec<_>>(),
+            upsert_targets => targets
+                .iter()
+                .map(|(suffix, columns)| minijinja::context! {
+                    suffix => suffix,
+                    columns => columns,
+                })
+                .collect::<Vec<_>>(),
             has_pk => !table.primary_key.is_empty(),
             has_auto_generated_pk => table.has_auto_generated_pk(),
-            imports => collect_table_imports(table, schema),
+            has_max_length => table.columns.iter().any(|col| max_length(&col.data_type).is_some()),
+            imports => imports,
+            returning_order => returning_order(table),
+        };
+
+        // Model classes are rendered once and embedded by every table template
+        let models = self
+            .env
+            .get_template("models")
+            .and_then(|template| template.render(&ctx))
+            .map_err(|e| SqliftError::CodeGen {
+                table: table.name.clone(),
+                message: format!("Render error: {}", e),
+            })?;
+
+        Ok(minijinja::context! {
+            model_classes => format!("{}\n\n\n", models.trim_end()),
+            ..ctx
         })
     }
 }
 
+/// How bulk inserts put RETURNING rows back in input order
+///
+/// Rows are matched by primary key when the caller supplies it, or sorted
+/// by a serial primary key, whose values follow the insert order. Other
+/// tables insert one row per statement.
+fn returning_order(table: &Table) -> Option<&'static str> {
+    let pk_columns = table.primary_key_columns();
+    let insert_columns = table.insert_columns();
+    if !pk_columns.is_empty()
+        && pk_columns
+            .iter()
+            .all(|pk| insert_columns.iter().any(|col| col.name == pk.name))
+    {
+        return Some("key");
+    }
+    match pk_columns.as_slice() {
+        [pk] if pk.is_auto_generated
+            && matches!(
+                pk.data_type,
+                DataType::SmallInt | DataType::Integer | DataType::BigInt
+            ) =>
+        {
+            Some("sequence")
+        }
+        _ => None,
+    }
+}
+
+/// Template name of a model class library
+fn models_name(models: PythonModels) -> &'static str {
+    match models {
+        PythonModels::Dataclass => "dataclass",
+        PythonModels::Pydantic => "pydantic",
+        PythonModels::Attrs => "attrs",
+        PythonModels::Msgspec => "msgspec",
+    }
+}
+
+/// Template name of a database driver
+fn driver_name(driver: PythonDriver) -> &'static str {
+    match driver {
+        PythonDriver::Psycopg => "psycopg",
+        PythonDriver::Psycopg2 => "psycopg2",
+    }
+}
+
+/// Import of the base class used by model classes
+///
+/// Dataclasses need nothing beyond the `dataclass` import every module has
+/// for filters.
+fn model_import(models: PythonModels) -> Option<&'static str> {
+    match models {
+        PythonModels::Dataclass => None,
+        PythonModels::Pydantic => Some("from pydantic import BaseModel"),
+        PythonModels::Attrs => Some("import attrs"),
+        PythonModels::Msgspec => Some("from msgspec import Struct"),
+    }
+}
+
+/// Maximum string length enforced by a column type
+fn max_length(data_type: &DataType) -> Option<u32> {
+    match data_type {
+        DataType::Varchar(len) | DataType::Char(len) => *len,
+        _ => None,
+    }
+}
+
+/// Optimistic locking column of a table
+#[derive(Debug, Clone, PartialEq)]
+enum VersionColumn<'a> {
+    /// Integer column incremented by every update
+    Integer(&'a str),
+    /// Postgres system column changed by every row write
+    Xmin,
+}
+
+impl VersionColumn<'_> {
+    fn context(&self) -> minijinja::Value {
+        match self {
+            VersionColumn::Integer(name) => minijinja::context! {
+                name => name,
+                is_xmin => false,
+                condition => format!("{} = %s", name),
+            },
+            VersionColumn::Xmin => minijinja::context! {
+                name => "xmin",
+                is_xmin => true,
+                condition => "xmin = %s::text::xid",
+            },
+        }
+    }
+}
+
+/// Resolve the optimistic locking column configured for a table
+///
+/// The global column only applies to tables that have it, while a per-table
+/// column must exist and be an integer. Tables without a primary key have no
+/// update or delete functions to guard.
+fn version_column<'a>(
+    table: &'a Table,
+    config: &CodeGenConfig,
+) -> Result<Option<VersionColumn<'a>>, SqliftError> {
+    if table.primary_key.is_empty() {
+        return Ok(None);
+    }
+
+    let Some(name) = config.version_column_for(&table.name) else {
+        return Ok(None);
+    };
+
+    if name == "xmin" {
+        return Ok(Some(VersionColumn::Xmin));
+    }
+
+    let explicit = config.table_version_columns.contains_key(&table.name);
+    let Some(column) = table.columns.iter().find(|col| col.name == name) else {
+        if explicit {
+            return Err(SqliftError::CodeGen {
+                table: table.name.clone(),
+                message: format!("Version column {} does not exist", name),
+            });
+        }
+        return Ok(None);
+    };
+
+    let is_integer = matches!(
+        column.data_type,
+        DataType::SmallInt | DataType::Integer | DataType::BigInt
+    );
+    if !is_integer || table.primary_key.contains(&column.name) {
+        if explicit {
+            return Err(SqliftError::CodeGen {
+                table: table.name.clone(),
+                message: format!("Version column {} must be a non-key integer column", name),
+            });
+        }
+        warn!(table = ?table.name, column = ?name, "Ignoring unsuitable version column");
+        return Ok(None);
+    }
+
+    Ok(Some(VersionColumn::Integer(&column.name)))
+}
+
+/// Resolve the soft delete column configured for a table
+///
+/// As with version columns, the global column only applies to tables that
+/// have it. The column must be a nullable timestamp.
+fn soft_delete_column<'a>(
+    table: &'a Table,
+    config: &CodeGenConfig,
+) -> Result<Option<&'a str>, SqliftError> {
+    let Some(name) = config.soft_delete_column_for(&table.name) else {
+        return Ok(None);
+    };
+
+    let explicit = config.table_soft_delete_columns.contains_key(&table.name);
+    let Some(column) = table.columns.iter().find(|col| col.name == name) else {
+        if explicit {
+            return Err(SqliftError::CodeGen {
+                table: table.name.clone(),
+                message: format!("Soft delete column {} does not exist", name),
+            });
+        }
+        return Ok(None);
+    };
+
+    let is_timestamp = matches!(
+        column.data_type,
+        DataType::Timestamp | DataType::TimestampTz
+    );
+    if !is_timestamp || !column.is_nullable {
+        if explicit {
+            return Err(SqliftError::CodeGen {
+                table: table.name.clone(),
+                message: format!("Soft delete column {} must be a nullable timestamp", name),
+            });
+        }
+        warn!(table = ?table.name, column = ?name, "Ignoring unsuitable soft delete column");
+        return Ok(None);
+    }
+
+    Ok(Some(&column.name))
+}
+
+/// Repository attributes of the generated Database class
+///
+/// Only class style has repositories. Tables named like a Database member
+/// are left out, since the attribute would shadow it.
+fn database_repositories(schema: &Schema, config: &CodeGenConfig) -> Vec<minijinja::Value> {
+    const MEMBERS: [&str; 4] = ["conn", "connect", "close", "transaction"];
+
+    if config.function_style != FunctionStyle::Class {
+        return Vec::new();
+    }
+
+    schema
+        .tables
+        .iter()
+        .filter(|t| {
+            let reserved = MEMBERS.contains(&t.name.as_str());
+            if reserved {
+                warn!(table = ?t.name, "Skipping Database attribute that would shadow a member");
+            }
+            !reserved
+        })
+        .map(|t| {
+            minijinja::context! {
+                table_name => &t.name,
+                module_name => &t.name,
+                repository_name => format!("{}Repository", t.singular_class_name()),
+            }
+        })
+        .collect()
+}
+
+/// psycopg dumper class used to adapt dicts for JSON columns, if any
+///
+/// With both json and jsonb columns, this is the base of an untyped dumper.
+fn json_dumper(schema: &Schema) -> Option<&'static str> {
+    match json_column_types(schema) {
+        (true, _) => Some("JsonDumper"),
+        (false, true) => Some("JsonbDumper"),
+        (false, false) => None,
+    }
+}
+
+/// Whether dicts are dumped untyped, letting each column pick json or jsonb
+fn untyped_json(schema: &Schema) -> bool {
+    json_column_types(schema) == (true, true)
+}
+
+/// Whether the schema has json and jsonb columns
+fn json_column_types(schema: &Schema) -> (bool, bool) {
+    let has = |data_type: DataType| {
+        schema
+            .tables
+            .iter()
+            .flat_map(|t| &t.columns)
+            .any(|c| c.data_type == data_type)
+    };
+    (has(DataType::Json), has(DataType::JsonBinary))
+}
+
+/// Explicit column list for SELECT and RETURNING clauses
+///
+/// Columns are listed in record field order so rows can be unpacked
+/// positionally, and new database columns don't break existing code.
+fn select_list(table: &Table) -> String {
+    table
+        .columns
+        .iter()
+        .map(|col| col.name.as_str())
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+/// Conflict targets for bulk upserts as (function name suffix, columns)
+///
+/// The primary key is a target unless it is auto-generated, and every unique
+/// constraint over writable columns is a target named after its columns
+/// (e.g. `_by_email`).
+fn upsert_targets(table: &Table) -> Vec<(String, Vec<String>)> {
+    let mut targets = Vec::new();
+
+    if !table.primary_key.is_empty() && !table.has_auto_generated_pk() {
+        targets.push((String::new(), table.primary_key.clone()));
+    }
+
+    let writable: Vec<&str> = table
+        .upsert_columns()
+        .iter()
+        .map(|col| col.name.as_str())
+        .collect();
+
+    for constraint in &table.unique_constraints {
+        if constraint
+            .columns
+            .iter()
+            .all(|col| writable.contains(&col.as_str()))
+        {
+            let suffix = format!("_by_{}", constraint.columns.join("_and_"));
+            targets.push((suffix, constraint.columns.clone()));
+        }
+    }
+
+    targets
+}
+
 /// Build template context for a column
 fn build_column_context(col: &Column, schema: &Schema) -> minijinja::Value {
     let base_type = python_type(&col.data_type, false, schema);
@@ -352,6 +917,35 @@ fn build_column_context(col: &Column, schema: &Schema) -> minijinja::Value {
         is_nullable => col.is_nullable,
         has_default => col.has_default,
         is_auto_generated => col.is_auto_generated,
+        max_length => max_length(&col.data_type),
+        pg_type => pg_type_name(&col.data_type),
+    }
+}
+
+/// PostgreSQL type name used to select binary dumpers for COPY
+fn pg_type_name(data_type: &DataType) -> String {
+    match data_type {
+        DataType::SmallInt => "int2".to_string(),
+        DataType::Integer => "int4".to_string(),
+        DataType::BigInt => "int8".to_string(),
+        DataType::Boolean => "bool".to_string(),
+        DataType::Text => "text".to_string(),
+        DataType::Varchar(_) => "varchar".to_string(),
+        DataType::Char(_) => "bpchar".to_string(),
+        DataType::Real => "float4".to_string(),
+        DataType::DoublePrecision => "float8".to_string(),
+        DataType::Numeric => "numeric".to_string(),
+        DataType::Timestamp => "timestamp".to_string(),
+        DataType::TimestampTz => "timestamptz".to_string(),
+        DataType::Date => "date".to_string(),
+        DataType::Time => "time".to_string(),
+        DataType::TimeTz => "timetz".to_string(),
+        DataType::Uuid => "uuid".to_string(),
+        DataType::Json => "json".to_string(),
+        DataType::JsonBinary => "jsonb".to_string(),
+        DataType::Binary => "bytea".to_string(),
+        DataType::Array(inner) => format!("{}[]", pg_type_name(inner)),
+        DataType::Enum(name) => name.clone(),
     }
 }
 
@@ -391,12 +985,131 @@ fn python_type(data_type: &DataType, is_nullable: bool, schema: &Schema) -> Stri
     }
 }
 
+/// Kind of Python value built by [`sample_value`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum SampleValue {
+    /// What the database fills in for an omitted column, stored by fakes
+    Default,
+    /// A valid value to insert, built by test factories
+    Fake,
+}
+
+/// Python expression for a sample value of a column type
+///
+/// Defaults are a fake repository's sequence for integers, the current
+/// time, or an empty value. Fake strings and integers are random so repeated
+/// inserts don't collide on unique constraints; other types use fixed values
+/// that round-trip exactly. Both use an enum's first value, collecting its
+/// class name in `enums` since it comes from the generated module.
+fn sample_value(
+    data_type: &DataType,
+    kind: SampleValue,
+    schema: &Schema,
+    imports: &mut BTreeSet<String>,
+    enums: &mut BTreeSet<String>,
+) -> String {
+    let fake = kind == SampleValue::Fake;
+    let mut import = |imp: &str| {
+        imports.insert(imp.to_string());
+    };
+
+    match data_type {
+        DataType::SmallInt | DataType::Integer | DataType::BigInt if !fake => {
+            "next(self._sequence)".to_string()
+        }
+        DataType::SmallInt => "fake_int(32_767)".to_string(),
+        DataType::Integer => "fake_int()".to_string(),
+        DataType::BigInt => "fake_int(9_223_372_036_854_775_807)".to_string(),
+        DataType::Boolean if fake => "True".to_string(),
+        DataType::Boolean => "False".to_string(),
+        DataType::Text | DataType::Varchar(_) | DataType::Char(_) if !fake => "\"\"".to_string(),
+        DataType::Text | DataType::Varchar(None) => "fake_str()".to_string(),
+        DataType::Varchar(Some(len)) => format!("fake_str({})", len),
+        DataType::Char(len) => format!("fake_str({}, exact=True)", len.unwrap_or(1)),
+        DataType::Real | DataType::DoublePrecision if fake => "1.5".to_string(),
+        DataType::Real | DataType::DoublePrecision => "0.0".to_string(),
+        DataType::Numeric => {
+            import("from decimal import Decimal");
+            if fake { "Decimal(\"1\")" } else { "Decimal(0)" }.to_string()
+        }
+        DataType::Timestamp => {
+            import("from datetime import datetime");
+            if fake {
+                "datetime(2024, 1, 2, 3, 4, 5)"
+            } else {
+                "datetime.now()"
+            }
+            .to_string()
+        }
+        DataType::TimestampTz => {
+            import("from datetime import datetime");
+            import("from datetime import timezone");
+            if fake {
+                "datetime(2024, 1, 2, 3, 4, 5, tzinfo=timezone.utc)"
+            } else {
+                "datetime.now(timezone.utc)"
+            }
+            .to_string()
+        }
+        DataType::Date => {
+            import("from datetime import date");
+            if fake {
+                "date(2024, 1, 2)"
+            } else {
+                "date.today()"
+            }
+            .to_string()
+        }
+        DataType::Time if fake => {
+            import("from datetime import time");
+            "time(3, 4, 5)".to_string()
+        }
+        DataType::Time => {
+            import("from datetime import datetime");
+            "datetime.now().time()".to_string()
+        }
+        DataType::TimeTz if fake => {
+            import("from datetime import time");
+            import("from datetime import timezone");
+            "time(3, 4, 5, tzinfo=timezone.utc)".to_string()
+        }
+        DataType::TimeTz => {
+            import("from datetime import datetime");
+            import("from datetime import timezone");
+            "datetime.now(timezone.utc).timetz()".to_string()
+        }
+        DataType::Uuid => {
+            import("from uuid import uuid4");
+            "uuid4()".to_string()
+        }
+        DataType::Json | DataType::JsonBinary if fake => "{\"key\": \"value\"}".to_string(),
+        DataType::Json | DataType::JsonBinary => "{}".to_string(),
+        DataType::Binary if fake => "b\"sqlift\"".to_string(),
+        DataType::Binary => "b\"\"".to_string(),
+        DataType::Array(inner) if fake => {
+            format!("[{}]", sample_value(inner, kind, schema, imports, enums))
+        }
+        DataType::Array(_) => "[]".to_string(),
+        DataType::Enum(name) => match schema.enums.iter().find(|e| &e.name == name) {
+            Some(enum_type) if !enum_type.values.is_empty() => {
+                let class_name = to_pascal_case(name);
+                let value = format!("{}.{}", class_name, enum_type.values[0].to_uppercase());
+                enums.insert(class_name);
+                value
+            }
+            // Unknown enums are typed as str, but no label is known to be valid
+            _ if fake => "fake_str()".to_string(),
+            _ => "\"\"".to_string(),
+        },
+    }
+}
+
 /// Collect required imports for a table
-fn collect_table_imports(table: &Table, schema: &Schema) -> Vec<String> {
+fn collect_table_imports(table: &Table, schema: &Schema, models: PythonModels) -> Vec<String> {
     let mut imports = HashSet::new();
 
     for col in &table.columns {
-        collect_type_imports(&col.data_type, schema, &mut imports);
+        collect_type_imports(&col.data_type, schema, models, &mut imports);
     }
 
     let mut sorted: Vec<_> = imports.into_iter().collect();
@@ -405,12 +1118,12 @@ fn collect_table_imports(table: &Table, schema: &Schema) -> Vec<String> {
 }
 
 /// Collect required imports for the entire schema
-fn collect_imports(schema: &Schema) -> Vec<String> {
+fn collect_imports(schema: &Schema, models: PythonModels) -> Vec<String> {
     let mut imports = HashSet::new();
 
     for table in &schema.tables {
         for col in &table.columns {
-            collect_type_imports(&col.data_type, schema, &mut imports);
+            collect_type_imports(&col.data_type, schema, models, &mut imports);
         }
     }
 
@@ -420,7 +1133,28 @@ fn collect_imports(schema: &Schema) -> Vec<String> {
 }
 
 /// Collect imports needed for a specific data type
-fn collect_type_imports(data_type: &DataType, schema: &Schema, imports: &mut HashSet<String>) {
+///
+/// Length-limited strings also need the model library's constraint types.
+fn collect_type_imports(
+    data_type: &DataType,
+    schema: &Schema,
+    models: PythonModels,
+    imports: &mut HashSet<String>,
+) {
+    if max_length(data_type).is_some() {
+        match models {
+            PythonModels::Pydantic => {
+                imports.insert("from pydantic import Field".to_string());
+            }
+            PythonModels::Msgspec => {
+                imports.insert("from msgspec import Meta".to_string());
+                imports.insert("from typing import Annotated".to_string());
+            }
+            // Dataclasses aren't validated and attrs uses a shared validator
+            PythonModels::Dataclass | PythonModels::Attrs => {}
+        }
+    }
+
     match data_type {
         DataType::Numeric => {
             imports.insert("from decimal import Decimal".to_string());
@@ -441,13 +1175,12 @@ fn collect_type_imports(data_type: &DataType, schema: &Schema, imports: &mut Has
             imports.insert("from typing import Any".to_string());
         }
         DataType::Array(inner) => {
-            collect_type_imports(inner, schema, imports);
+            // Array elements have no length constraints
+            collect_type_imports(inner, schema, PythonModels::Dataclass, imports);
         }
-        DataType::Enum(name) => {
-            // Only import if it's a known enum
-            if schema.enums.iter().any(|e| &e.name == name) {
-                imports.insert(format!("from .enums import {}", to_pascal_case(name)));
-            }
+        // Only import if it's a known enum
+        DataType::Enum(name) if schema.enums.iter().any(|e| &e.name == name) => {
+            imports.insert(format!("from .enums import {}", to_pascal_case(name)));
         }
         _ => {}
     }
@@ -456,6 +1189,8 @@ fn collect_type_imports(data_type: &DataType, schema: &Schema, imports: &mut Has
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::schema::UniqueConstraint;
+    use std::path::{Path, PathBuf};
 
     fn empty_schema() -> Schema {
         Schema {
@@ -514,6 +1249,881 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_sample_value() {
+        let schema = Schema {
+            name: "public".to_string(),
+            tables: vec![],
+            enums: vec![EnumType {
+                name: "order_status".to_string(),
+                values: vec!["pending".to_string(), "shipped".to_string()],
+            }],
+        };
+        let mut imports = BTreeSet::new();
+        let mut enums = BTreeSet::new();
+        let mut sample = |data_type: DataType, kind: SampleValue| {
+            sample_value(&data_type, kind, &schema, &mut imports, &mut enums)
+        };
+
+        // Defaults stand in for what the database would fill in
+        assert_eq!(
+            sample(DataType::BigInt, SampleValue::Default),
+            "next(self._sequence)"
+        );
+        assert_eq!(sample(DataType::Uuid, SampleValue::Default), "uuid4()");
+        assert_eq!(
+            sample(
+                DataType::Array(Box::new(DataType::Text)),
+                SampleValue::Default
+            ),
+            "[]"
+        );
+        assert_eq!(
+            sample(DataType::Enum("unknown".to_string()), SampleValue::Default),
+            "\"\""
+        );
+
+        // Fake values must be valid inserts
+        assert_eq!(
+            sample(DataType::Varchar(Some(20)), SampleValue::Fake),
+            "fake_str(20)"
+        );
+        assert_eq!(
+            sample(DataType::Char(Some(3)), SampleValue::Fake),
+            "fake_str(3, exact=True)"
+        );
+        assert_eq!(
+            sample(DataType::TimestampTz, SampleValue::Fake),
+            "datetime(2024, 1, 2, 3, 4, 5, tzinfo=timezone.utc)"
+        );
+        assert_eq!(
+            sample(DataType::Enum("unknown".to_string()), SampleValue::Fake),
+            "fake_str()"
+        );
+
+        // Both use an enum's first value
+        let status = DataType::Enum("order_status".to_string());
+        assert_eq!(
+            sample(status.clone(), SampleValue::Default),
+            "OrderStatus.PENDING"
+        );
+        assert_eq!(
+            sample(DataType::Array(Box::new(status)), SampleValue::Fake),
+            "[OrderStatus.PENDING]"
+        );
+
+        assert!(imports.contains("from uuid import uuid4"));
+        assert!(imports.contains("from datetime import timezone"));
+        assert_eq!(enums, BTreeSet::from(["OrderStatus".to_string()]));
+    }
+
+    fn users_schema() -> Schema {
+        Schema {
+            name: "public".to_string(),
+            tables: vec![Table {
+                name: "users".to_string(),
+                columns: vec![
+                    Column {
+                        name: "id".to_string(),
+                        data_type: DataType::Integer,
+                        is_nullable: false,
+                        has_default: true,
+                        is_auto_generated: true,
+                    },
+                    Column {
+                        name: "email".to_string(),
+                        data_type: DataType::Text,
+                        is_nullable: false,
+                        has_default: false,
+                        is_auto_generated: false,
+                    },
+                ],
+                primary_key: vec!["id".to_string()],
+                unique_constraints: vec![],
+                foreign_keys: vec![],
+            }],
+            enums: vec![],
+        }
+    }
+
+    #[test]
+    fn test_render_library() {
+        let config = CodeGenConfig::new(PathBuf::from("out/database"));
+        let files = PythonGenerator::new()
+            .render(&users_schema(), &config)
+            .unwrap();
+
+        assert_eq!(files.root(), Path::new("out"));
+        let paths: Vec<_> = files.iter().map(|(path, _)| path.to_path_buf()).collect();
+        assert_eq!(
+            paths,
+            vec![
+                PathBuf::from("database/__init__.py"),
+                PathBuf::from("database/_connection.py"),
+                PathBuf::from("database/_database.py"),
+                PathBuf::from("database/_types.py"),
+                PathBuf::from("database/users.py"),
+            ]
+        );
+
+        let users = files.get("database/users.py").unwrap();
+        assert!(users.contains("class UserRecord:"));
+        assert!(users.contains("def get_user_by_id("));
+        assert!(users.contains("SELECT id, email FROM users"));
+        assert!(!users.contains("SELECT *"));
+        assert!(!users.contains("RETURNING *"));
+        assert!(users.contains("class UserFilter:"));
+        assert!(users.contains("    email: ColumnFilter[str] | None = None"));
+        assert!(users.contains("def find_users("));
+        assert!(users.contains("def count_users("));
+        assert!(users.contains("def exists_user_by_id("));
+        assert!(users.contains("-> Page[UserRecord]:"));
+        assert!(users.contains("_USERS_NULLABLE: frozenset[str] = frozenset()\n"));
+    }
+
+    #[test]
+    fn test_render_explicit_columns() {
+        let mut schema = users_schema();
+        schema.tables[0].columns.push(Column {
+            name: "nickname".to_string(),
+            data_type: DataType::Text,
+            is_nullable: true,
+            has_default: false,
+            is_auto_generated: false,
+        });
+        let config = CodeGenConfig::new(PathBuf::from("out/database"));
+        let files = PythonGenerator::new().render(&schema, &config).unwrap();
+
+        // Columns are named in record field order in every query
+        let users = files.get("database/users.py").unwrap();
+        assert!(users.contains("    id: int\n    email: str\n    nickname: str | None\n"));
+        assert!(users.contains("\"SELECT id, email, nickname FROM users WHERE id = %s\""));
+        assert!(users.contains("        RETURNING id, email, nickname\n"));
+        assert!(!users.contains("SELECT *"));
+        assert!(!users.contains("RETURNING *"));
+
+        // Rows are unpacked positionally from cursors that return tuples
+        assert!(users.contains("        return cls(*row)\n"));
+        let types = files.get("database/_types.py").unwrap();
+        assert!(types
+            .contains("    return conn.cursor(row_factory=tuple_row).execute(query, params)\n"));
+
+        let config = config.with_python_driver(PythonDriver::Psycopg2);
+        let files = PythonGenerator::new().render(&schema, &config).unwrap();
+        let types = files.get("database/_types.py").unwrap();
+        assert!(types.contains("    cursor = conn.cursor(cursor_factory=Cursor)\n"));
+    }
+
+    #[test]
+    fn test_render_find() {
+        let mut schema = users_schema();
+        schema.tables[0].columns[1].data_type = DataType::Varchar(Some(255));
+        let config = CodeGenConfig::new(PathBuf::from("out/database"));
+        let files = PythonGenerator::new().render(&schema, &config).unwrap();
+
+        // Filters have one optional ColumnFilter per column
+        let users = files.get("database/users.py").unwrap();
+        assert!(users.contains(
+            "class UserFilter:\n    \"\"\"Filter for users queries.\n\n    \
+             Conditions on different columns are combined with AND.\n    \"\"\"\n    \
+             id: ColumnFilter[int] | None = None\n    email: ColumnFilter[str] | None = None\n"
+        ));
+        assert!(users.contains(
+            "def find_users(\n    conn: Connection,\n    *,\n    where: UserFilter | None = None,\n    \
+             order_by: str | Sequence[OrderBy] | None = None,\n    limit: int | None = None,\n    \
+             offset: int | None = None,\n) -> list[UserRecord]:"
+        ));
+        assert!(users.contains("    where_sql, params = _compile_where(where, _USERS_COLUMNS)\n"));
+        assert!(users
+            .contains("sql.SQL(\"SELECT id, email FROM users{}\").format(sql.Composed(clauses))"));
+
+        let class = PythonGenerator::new()
+            .render(
+                &schema,
+                &config.clone().with_function_style(FunctionStyle::Class),
+            )
+            .unwrap();
+        assert!(class.get("database/users.py").unwrap().contains(
+            "    def find(\n        self,\n        *,\n        where: UserFilter | None = None,\n        \
+             order_by: str | Sequence[OrderBy] | None = None,\n        limit: int | None = None,\n        \
+             offset: int | None = None,\n    ) -> list[UserRecord]:\n"
+        ));
+
+        // Every operator binds its value as a parameter
+        let types = files.get("database/_types.py").unwrap();
+        assert!(types.contains("class ColumnFilter(Generic[T]):"));
+        for (name, template) in [
+            ("eq", "{} = {}"),
+            ("in_", "{} = ANY({})"),
+            ("lt", "{} < {}"),
+            ("lte", "{} <= {}"),
+            ("gt", "{} > {}"),
+            ("gte", "{} >= {}"),
+            ("like", "{} LIKE {}"),
+        ] {
+            assert!(types.contains(&format!("    (\"{name}\", \"{template}\"),\n")));
+        }
+        assert!(types.contains("\"{} IS NULL\" if column_filter.is_null else \"{} IS NOT NULL\""));
+        assert!(types
+            .contains("            raise ValueError(\n                f\"Invalid filter column: "));
+
+        let init = files.get("database/__init__.py").unwrap();
+        assert!(init.contains("    UserFilter,\n"));
+        assert!(init.contains("    find_users,\n"));
+    }
+
+    #[test]
+    fn test_render_count_exists() {
+        let config = CodeGenConfig::new(PathBuf::from("out/database"));
+        let files = PythonGenerator::new()
+            .render(&users_schema(), &config)
+            .unwrap();
+
+        let users = files.get("database/users.py").unwrap();
+        assert!(users.contains(
+            "def count_users(\n    conn: Connection,\n    *,\n    where: UserFilter | None = None,\n) -> int:"
+        ));
+        assert!(users.contains("sql.SQL(\"SELECT count(*) FROM users{}\").format(where_sql)"));
+        assert!(users
+            .contains("def exists_user_by_id(\n    conn: Connection,\n    id: int,\n) -> bool:"));
+        assert!(users.contains("\"SELECT EXISTS (SELECT 1 FROM users WHERE id = %s)\""));
+
+        let class = PythonGenerator::new()
+            .render(
+                &users_schema(),
+                &config.clone().with_function_style(FunctionStyle::Class),
+            )
+            .unwrap();
+        let users = class.get("database/users.py").unwrap();
+        assert!(users.contains("    def count(self, *, where: UserFilter | None = None) -> int:\n"));
+        assert!(users
+            .contains("    def exists_by_id(\n        self,\n        id: int,\n    ) -> bool:\n"));
+
+        let init = files.get("database/__init__.py").unwrap();
+        assert!(init.contains("    count_users,\n"));
+        assert!(init.contains("    exists_user_by_id,\n"));
+    }
+
+    #[test]
+    fn test_render_keyset_pagination() {
+        let mut schema = users_schema();
+        schema.tables[0].columns[1].is_nullable = true;
+        let mut user_roles = schema.tables[0].clone();
+        user_roles.name = "user_roles".to_string();
+        user_roles.columns[0].name = "user_id".to_string();
+        user_roles.columns[1].name = "role".to_string();
+        user_roles.columns[1].is_nullable = false;
+        user_roles.primary_key = vec!["user_id".to_string(), "role".to_string()];
+        schema.tables.push(user_roles);
+
+        let config = CodeGenConfig::new(PathBuf::from("out/database"));
+        let files = PythonGenerator::new().render(&schema, &config).unwrap();
+        let users = files.get("database/users.py").unwrap();
+        assert!(users.contains(
+            "def get_users_page(\n    conn: Connection,\n    *,\n    limit: int = 100,\n    \
+             order_by: str | Sequence[OrderBy] | None = None,\n    \
+             after: str | Sequence[Any] | None = None,\n    where: UserFilter | None = None,\n\
+             ) -> Page[UserRecord]:\n"
+        ));
+        let class = PythonGenerator::new()
+            .render(
+                &schema,
+                &config.clone().with_function_style(FunctionStyle::Class),
+            )
+            .unwrap();
+        assert!(class.get("database/users.py").unwrap().contains(
+            "    def get_page(\n        self,\n        *,\n        limit: int = 100,\n        \
+             order_by: str | Sequence[OrderBy] | None = None,\n        \
+             after: str | Sequence[Any] | None = None,\n        where: UserFilter | None = None,\n    \
+             ) -> Page[UserRecord]:\n"
+        ));
+
+        // The primary key breaks ties, and nullable columns get NULL handling
+        assert!(users.contains("frozenset({\"email\"})"));
+        assert!(users.contains("_keyset_order(order_by, (\"id\",), _USERS_COLUMNS)"));
+        assert!(users.contains("_compile_keyset(order, values, _USERS_NULLABLE)"));
+        let user_roles = files.get("database/user_roles.py").unwrap();
+        assert!(user_roles
+            .contains("_keyset_order(order_by, (\"user_id\", \"role\"), _USER_ROLES_COLUMNS)"));
+
+        // Cursors round-trip the sort order and values of the last row
+        assert!(users.contains("_decode_cursor(after, order)"));
+        assert!(users.contains("_encode_cursor(order, "));
+
+        // The predicate is an OR of branches, each an AND of equalities on
+        // the earlier sort columns and one comparison that handles NULLs
+        let types = files.get("database/_types.py").unwrap();
+        let keyset = &types[types.find("def _compile_keyset(").unwrap()..];
+        let keyset = &keyset[..keyset.find("\n\n\ndef ").unwrap()];
+        let fragments: Vec<_> = keyset
+            .split("sql.SQL(\"")
+            .skip(1)
+            .map(|rest| &rest[..rest.find("\")").unwrap()])
+            .collect();
+        assert_eq!(
+            fragments,
+            vec![
+                "{} IS NOT NULL",
+                "({} > {} OR {} IS NULL)",
+                "{} {} {}",
+                "{} IS NULL",
+                "{} = {}",
+                "({})",
+                " AND ",
+                "FALSE",
+                "({})",
+                " OR ",
+            ]
+        );
+
+        // The codec rejects cursors from another sort order
+        assert!(types.contains("\"Pagination cursor was created with a different sort order\""));
+        for kind in ["decimal", "uuid", "datetime", "date", "time", "bytes"] {
+            assert!(types.contains(&format!("        return {{\"{kind}\": ")));
+            assert!(types.contains(&format!("    if kind == \"{kind}\":\n")));
+        }
+    }
+
+    #[test]
+    fn test_render_repository_protocol() {
+        let config = CodeGenConfig::new(PathBuf::from("out/database"))
+            .with_function_style(FunctionStyle::Class);
+        let files = PythonGenerator::new()
+            .render(&users_schema(), &config)
+            .unwrap();
+
+        let users = files.get("database/users.py").unwrap();
+        assert!(users.contains("class UserRepositoryProtocol(Protocol):"));
+        assert!(users.contains("    ) -> UserRecord | None: ...\n"));
+        assert!(
+            users.contains("    def count(self, *, where: UserFilter | None = None) -> int: ...\n")
+        );
+        // Describes the methods only, not the connection
+        let start = users.find("class UserRepositoryProtocol").unwrap();
+        let end = users.find("class UserRepository:").unwrap();
+        assert!(start < end);
+        assert!(users[start..end].contains("    def batch_update("));
+        assert!(!users[start..end].contains("conn"));
+
+        let init = files.get("database/__init__.py").unwrap();
+        assert!(init.contains("    \"UserRepositoryProtocol\",\n"));
+    }
+
+    #[test]
+    fn test_render_flat() {
+        let config = CodeGenConfig::new(PathBuf::from("db")).with_output_mode(OutputMode::Flat);
+        let files = PythonGenerator::new()
+            .render(&users_schema(), &config)
+            .unwrap();
+
+        assert_eq!(files.len(), 1);
+        assert!(files.get("db.py").unwrap().contains("def insert_user("));
+    }
+
+    #[test]
+    fn test_pg_type_name() {
+        assert_eq!(pg_type_name(&DataType::Integer), "int4");
+        assert_eq!(pg_type_name(&DataType::Varchar(Some(10))), "varchar");
+        assert_eq!(
+            pg_type_name(&DataType::Array(Box::new(DataType::Uuid))),
+            "uuid[]"
+        );
+        assert_eq!(
+            pg_type_name(&DataType::Enum("order_status".to_string())),
+            "order_status"
+        );
+    }
+
+    #[test]
+    fn test_render_connection() {
+        let mut schema = users_schema();
+        schema.enums.push(EnumType {
+            name: "user_role".to_string(),
+            values: vec!["admin".to_string()],
+        });
+        schema.tables[0].columns[1].data_type = DataType::JsonBinary;
+
+        let config = CodeGenConfig::new(PathBuf::from("out/database"));
+        let code = PythonGenerator::new()
+            .render_connection(&schema, &config)
+            .unwrap();
+        assert!(code.contains("from .enums import (\n    UserRole,\n)"));
+        assert!(code.contains(r#"_register_enum(conn, "public.user_role", UserRole)"#));
+        assert!(code.contains("conn.adapters.register_dumper(dict, JsonbDumper)"));
+        assert!(!code.contains("_UntypedJsonDumper"));
+
+        // With json and jsonb columns, Postgres picks the type per column
+        let mut mixed = schema.clone();
+        let mut events = mixed.tables[0].clone();
+        events.name = "events".to_string();
+        events.columns[1].data_type = DataType::Json;
+        mixed.tables.push(events);
+        let code = PythonGenerator::new()
+            .render_connection(&mixed, &config)
+            .unwrap();
+        assert!(code.contains("from psycopg.types.json import JsonDumper, "));
+        assert!(code.contains("class _UntypedJsonDumper(JsonDumper):"));
+        assert!(code.contains("conn.adapters.register_dumper(dict, _UntypedJsonDumper)"));
+        let files = PythonGenerator::new().render(&mixed, &config).unwrap();
+        let events = files.get("database/events.py").unwrap();
+        assert!(events.contains(r#"sql.SQL("{} = CASE WHEN v.{} THEN v.{}::json ELSE t.{} END")"#));
+
+        let code = PythonGenerator::new()
+            .render_connection(&users_schema(), &config)
+            .unwrap();
+        assert!(!code.contains("register_enum"));
+        assert!(!code.contains("register_dumper"));
+
+        let config = config.with_python_driver(PythonDriver::Psycopg2);
+        let code = PythonGenerator::new()
+            .render_connection(&schema, &config)
+            .unwrap();
+        assert!(code.contains(r#"_register_enum(conn, "public.user_role", UserRole)"#));
+        assert!(code.contains("psycopg2.extensions.register_adapter(dict, Json)"));
+    }
+
+    #[test]
+    fn test_render_database() {
+        let mut schema = users_schema();
+        let mut reserved = schema.tables[0].clone();
+        reserved.name = "transaction".to_string();
+        schema.tables.push(reserved);
+
+        let config = CodeGenConfig::new(PathBuf::from("out/database"));
+        let code = PythonGenerator::new()
+            .render_database(&schema, &config)
+            .unwrap();
+        assert!(code.contains("class Database:"));
+        assert!(code.contains("        with self.conn.transaction():\n            yield"));
+        // Standalone functions have no repositories to expose
+        assert!(!code.contains("Repository"));
+
+        let config = config.with_function_style(FunctionStyle::Class);
+        let code = PythonGenerator::new()
+            .render_database(&schema, &config)
+            .unwrap();
+        assert!(code.contains("from .users import UserRepository"));
+        assert!(code.contains("        self.users = UserRepository(conn)"));
+        // Would shadow Database.transaction()
+        assert!(!code.contains("self.transaction ="));
+
+        let config = config.with_python_driver(PythonDriver::Psycopg2);
+        let code = PythonGenerator::new()
+            .render_database(&schema, &config)
+            .unwrap();
+        assert!(code.contains(r#"sql.SQL("SAVEPOINT {}").format(savepoint)"#));
+        assert!(code.contains("self.conn.commit()"));
+
+        // Flat output shares the class, but its repositories are stubs
+        let config = CodeGenConfig::new(PathBuf::from("db"))
+            .with_output_mode(OutputMode::Flat)
+            .with_function_style(FunctionStyle::Class);
+        let files = PythonGenerator::new().render(&schema, &config).unwrap();
+        let code = files.get("db.py").unwrap();
+        assert!(code.contains("class Database:\n    \"\"\"One connection with transactions.\n"));
+        assert!(code.contains("                user = insert_user(db.conn, email="));
+        assert!(!code.contains("self.users = UserRepository(conn)"));
+    }
+
+    #[test]
+    fn test_render_bulk_upsert() {
+        let mut schema = users_schema();
+        schema.tables[0].unique_constraints.push(UniqueConstraint {
+            name: "users_email_key".to_string(),
+            columns: vec!["email".to_string()],
+        });
+        let generator = PythonGenerator::new();
+        let render = |config: CodeGenConfig| generator.render(&schema, &config).unwrap();
+
+        let standalone = render(CodeGenConfig::new(PathBuf::from("out/database")));
+        let users = standalone.get("database/users.py").unwrap();
+        assert!(users.contains("def _bulk_upsert_users(\n    conn: Connection,\n"));
+        assert!(users.contains("raise ValueError(f\"Duplicate conflict key for upsert: {key!r}\")"));
+        // NULLs never conflict, so a key containing one is rejected up front
+        assert!(users.contains(
+            "        if None in key:\n            raise ValueError(f\"Conflict columns can't be NULL for upsert: {key!r}\")\n"
+        ));
+        // Returned rows are matched to their input by conflict key, and a
+        // key returned in another form raises instead of sorting last
+        assert!(users.contains(
+            "                    if key not in positions:\n                        raise RuntimeError(f\"Upserted users row {key!r} matches no input record\")\n"
+        ));
+        assert!(users.contains("upserted.append((positions[key], upserted_record))"));
+        assert!(!users.contains("positions.get("));
+        assert!(!users.contains("chunk[0][0]"));
+
+        // The class and flat outputs share the same helper
+        let class = render(
+            CodeGenConfig::new(PathBuf::from("out/database"))
+                .with_function_style(FunctionStyle::Class),
+        );
+        let users = class.get("database/users.py").unwrap();
+        assert!(users.contains("    def _bulk_upsert(\n        self,\n"));
+        assert!(users.contains("                cursor = _execute(self.conn, query, values)\n"));
+
+        let flat =
+            render(CodeGenConfig::new(PathBuf::from("db")).with_output_mode(OutputMode::Flat));
+        let code = flat.get("db.py").unwrap();
+        assert!(code.contains("def _bulk_upsert_users(\n    conn: Connection,\n"));
+        assert!(code.contains("raise ValueError(f\"Duplicate conflict key for upsert: {key!r}\")"));
+    }
+
+    #[test]
+    fn test_upsert_targets() {
+        let mut schema = users_schema();
+        let users = &mut schema.tables[0];
+        users.unique_constraints.push(UniqueConstraint {
+            name: "users_email_key".to_string(),
+            columns: vec!["email".to_string()],
+        });
+
+        // Auto-generated primary key is not a conflict target
+        assert_eq!(
+            upsert_targets(users),
+            vec![("_by_email".to_string(), vec!["email".to_string()])]
+        );
+
+        users.columns[0].is_auto_generated = false;
+        assert_eq!(
+            upsert_targets(users)[0],
+            (String::new(), vec!["id".to_string()])
+        );
+
+        let config = CodeGenConfig::new(PathBuf::from("out/database"));
+        let files = PythonGenerator::new().render(&schema, &config).unwrap();
+        let users = files.get("database/users.py").unwrap();
+        assert!(users.contains("class UserUpsertParams:"));
+        assert!(users.contains("def bulk_upsert_users("));
+        assert!(users.contains("def bulk_upsert_users_by_email_returning("));
+    }
+
+    #[test]
+    fn test_version_column() {
+        let mut schema = users_schema();
+        schema.tables[0].columns.push(Column {
+            name: "version".to_string(),
+            data_type: DataType::Integer,
+            is_nullable: false,
+            has_default: true,
+            is_auto_generated: false,
+        });
+        schema.tables[0].unique_constraints.push(UniqueConstraint {
+            name: "users_email_key".to_string(),
+            columns: vec!["email".to_string()],
+        });
+        let users = &schema.tables[0];
+
+        let config = CodeGenConfig::new(PathBuf::from("out/database"));
+        assert_eq!(version_column(users, &config).unwrap(), None);
+
+        let config = config.with_version_column("version");
+        assert_eq!(
+            version_column(users, &config).unwrap(),
+            Some(VersionColumn::Integer("version"))
+        );
+
+        let files = PythonGenerator::new().render(&schema, &config).unwrap();
+        let code = files.get("database/users.py").unwrap();
+        assert!(code.contains("    expected_version: int,\n    email: str | _Unset = UNSET,\n)"));
+        assert!(code.contains("version = version + 1"));
+        assert!(code.contains("WHERE id = %s AND version = %s RETURNING"));
+        assert!(code.contains("raise StaleRecordError(\"users\", (id,), expected_version)"));
+        // Other writes bump the version and can't set it
+        assert!(code.contains("class UserUpdateParams:\n    \"\"\"Parameters for batch updating a user record.\"\"\"\n    id: int\n    email: str | _Unset = UNSET\n\n"));
+        assert!(!code.contains("    version: int | _Unset = UNSET\n"));
+        // Once in update_user, once in bulk_update_users
+        assert_eq!(
+            code.matches("set_clauses.append(sql.SQL(\"version = version + 1\"))")
+                .count(),
+            2
+        );
+        assert!(code.contains("        sql.SQL(\"version = t.version + 1\"),\n"));
+        assert!(code.contains("+ [sql.SQL(\"version = users.version + 1\")]"));
+
+        let config = config.with_table_version_column("users", "xmin");
+        let files = PythonGenerator::new().render(&schema, &config).unwrap();
+        let code = files.get("database/users.py").unwrap();
+        assert!(code.contains("    xmin: int\n"));
+        assert!(code.contains("xmin::text::bigint AS xmin FROM users"));
+        assert!(code.contains("AND xmin = %s::text::xid"));
+
+        // Per-table columns must exist and be integers
+        let config = CodeGenConfig::new(PathBuf::from("out/database"))
+            .with_table_version_column("users", "email");
+        assert!(version_column(users, &config).is_err());
+        let config = CodeGenConfig::new(PathBuf::from("out/database"))
+            .with_table_version_column("users", "missing");
+        assert!(version_column(users, &config).is_err());
+    }
+
+    #[test]
+    fn test_render_bulk_insert_returning() {
+        let insert = "INSERT INTO users (email) SELECT v.email::text FROM (VALUES {}) AS v(_ordinal, email) ORDER BY _ordinal RETURNING id, email";
+
+        for driver in [PythonDriver::Psycopg, PythonDriver::Psycopg2] {
+            let config =
+                CodeGenConfig::new(PathBuf::from("out/database")).with_python_driver(driver);
+            let files = PythonGenerator::new()
+                .render(&users_schema(), &config)
+                .unwrap();
+
+            // Chunked multi-row inserts, sorted back by the serial key
+            let users = files.get("database/users.py").unwrap();
+            if driver == PythonDriver::Psycopg2 {
+                assert!(users.contains(&format!("        \"{}\",\n", insert.replace("{}", "%s"))));
+                assert!(users.contains(
+                    "        [(ordinal, record.email) for ordinal, record in enumerate(records)],\n        fetch=True,\n"
+                ));
+            } else {
+                assert!(users.contains("    chunk_size = _MAX_QUERY_PARAMS // 2\n"));
+                assert!(users.contains("        values.extend((ordinal, record.email))\n"));
+                assert!(users.contains("\"FROM (VALUES {}) AS v(_ordinal, email) \"\n"));
+            }
+            assert!(!users.contains("_execute_each"));
+            assert!(users.contains("        raise RuntimeError(f\"Inserted {len(records)} users records but {len(rows)} rows were returned\")\n"));
+            assert!(users.contains("    return sorted(inserted, key=lambda record: record.id)\n"));
+        }
+
+        // A key supplied by the caller matches returned rows back to the input
+        let mut schema = users_schema();
+        schema.tables[0].columns[0].has_default = false;
+        schema.tables[0].columns[0].is_auto_generated = false;
+        let config = CodeGenConfig::new(PathBuf::from("out/database"))
+            .with_function_style(FunctionStyle::Class);
+        let files = PythonGenerator::new().render(&schema, &config).unwrap();
+        let users = files.get("database/users.py").unwrap();
+        assert!(users.contains("        by_key = {(record.id,): record for record in inserted}\n"));
+        assert!(users.contains(
+            "                raise RuntimeError(f\"No users row was returned for key {key!r}\")\n"
+        ));
+
+        // Without a primary key, each record gets its own statement
+        let mut schema = users_schema();
+        schema.tables[0].primary_key.clear();
+        let config = CodeGenConfig::new(PathBuf::from("out/database"));
+        let files = PythonGenerator::new().render(&schema, &config).unwrap();
+        let users = files.get("database/users.py").unwrap();
+        assert!(users.contains(
+            "    rows = _execute_each(\n        conn,\n        \"INSERT INTO users (email) VALUES (%s) RETURNING id, email\",\n"
+        ));
+        assert!(users
+            .contains("            raise RuntimeError(\"Insert into users returned no row\")\n"));
+        let types = files.get("database/_types.py").unwrap();
+        assert!(types.contains("    cursor.executemany(query, rows, returning=True)\n"));
+        assert!(types.contains("        if not cursor.nextset():\n"));
+    }
+
+    #[test]
+    fn test_soft_delete_column() {
+        let mut schema = users_schema();
+        schema.tables[0].columns.push(Column {
+            name: "deleted_at".to_string(),
+            data_type: DataType::TimestampTz,
+            is_nullable: true,
+            has_default: false,
+            is_auto_generated: false,
+        });
+        let users = &schema.tables[0];
+
+        let config = CodeGenConfig::new(PathBuf::from("out/database"))
+            .with_soft_delete_column(crate::codegen::DEFAULT_SOFT_DELETE_COLUMN);
+        assert_eq!(
+            soft_delete_column(users, &config).unwrap(),
+            Some("deleted_at")
+        );
+
+        let files = PythonGenerator::new().render(&schema, &config).unwrap();
+        let code = files.get("database/users.py").unwrap();
+        assert!(code
+            .contains("UPDATE users SET deleted_at = now() WHERE id = %s AND deleted_at IS NULL"));
+        assert!(code.contains("def restore_user("));
+        assert!(code.contains("def hard_delete_user("));
+        assert!(code.contains("None if include_deleted else _USERS_NOT_DELETED"));
+        // Soft-deleted rows are opted into by keyword
+        assert!(code.contains(
+            "    offset: int | None = None,\n    include_deleted: bool = False,\n) -> list[UserRecord]:"
+        ));
+        assert!(code.contains(
+            "    where: UserFilter | None = None,\n    include_deleted: bool = False,\n) -> int:"
+        ));
+        assert!(
+            code.contains("    id: int,\n    *,\n    include_deleted: bool = False,\n) -> bool:")
+        );
+        // include_deleted is keyword-only, after get_all's positional limit/offset
+        assert!(code.contains(
+            "    offset: int | None = None,\n    *,\n    include_deleted: bool = False,\n) -> list[UserRecord]:"
+        ));
+        // Empty updates and conflicting upserts re-read the row even when it is soft-deleted
+        assert!(code.contains("        return get_user_by_id(conn, id, include_deleted=True)\n"));
+        let mut natural_key = schema.clone();
+        natural_key.tables[0].columns[0].is_auto_generated = false;
+        let files = PythonGenerator::new()
+            .render(&natural_key, &config)
+            .unwrap();
+        let upsert = files.get("database/users.py").unwrap();
+        assert!(
+            upsert.contains("        existing = get_user_by_id(conn, id, include_deleted=True)\n")
+        );
+        let init = files.get("database/__init__.py").unwrap();
+        assert!(init.contains("    restore_user,\n    hard_delete_user,\n"));
+
+        // Soft deletes and restores bump the version like any other update
+        schema.tables[0].columns.push(Column {
+            name: "version".to_string(),
+            data_type: DataType::Integer,
+            is_nullable: false,
+            has_default: true,
+            is_auto_generated: false,
+        });
+        let versioned = config.clone().with_version_column("version");
+        let files = PythonGenerator::new().render(&schema, &versioned).unwrap();
+        let code = files.get("database/users.py").unwrap();
+        assert!(code.contains(
+            "UPDATE users SET deleted_at = now(), version = version + 1 WHERE id = ANY(%s)"
+        ));
+        assert!(code
+            .contains("UPDATE users SET deleted_at = NULL, version = version + 1 WHERE id = %s"));
+
+        // Tables without the global column are left alone
+        let mut schema = users_schema();
+        assert_eq!(
+            soft_delete_column(&schema.tables[0], &config).unwrap(),
+            None
+        );
+
+        // Per-table columns must be nullable timestamps
+        schema.tables[0].columns[1].data_type = DataType::Timestamp;
+        let config = CodeGenConfig::new(PathBuf::from("out/database"))
+            .with_table_soft_delete_column("users", "email");
+        assert!(soft_delete_column(&schema.tables[0], &config).is_err());
+        schema.tables[0].columns[1].is_nullable = true;
+        assert_eq!(
+            soft_delete_column(&schema.tables[0], &config).unwrap(),
+            Some("email")
+        );
+    }
+
+    #[test]
+    fn test_render_pydantic_models() {
+        let mut schema = users_schema();
+        schema.tables[0].columns[1].data_type = DataType::Varchar(Some(255));
+
+        let config = CodeGenConfig::new(PathBuf::from("out/database"))
+            .with_python_models(PythonModels::Pydantic);
+        let files = PythonGenerator::new().render(&schema, &config).unwrap();
+
+        let users = files.get("database/users.py").unwrap();
+        assert!(users.contains("from pydantic import BaseModel\nfrom pydantic import Field\n"));
+        assert!(users.contains("class UserRecord(_RowModel):"));
+        assert!(users.contains("    email: str = Field(max_length=255)\n"));
+        assert!(users.contains("class UserUpdateParams(BaseModel):"));
+        assert!(users.contains("    email: str | _Unset = Field(default=UNSET, max_length=255)\n"));
+        // Filters stay dataclasses since they are compiled field by field
+        assert!(users.contains("@dataclass\nclass UserFilter:"));
+
+        let types = files.get("database/_types.py").unwrap();
+        assert!(types.contains("class _RowModel(BaseModel):"));
+        assert!(types.contains("def __get_pydantic_core_schema__("));
+
+        let config = config.with_python_models(PythonModels::Dataclass);
+        let files = PythonGenerator::new().render(&schema, &config).unwrap();
+        assert!(!files
+            .get("database/_types.py")
+            .unwrap()
+            .contains("pydantic"));
+        assert!(files
+            .get("database/users.py")
+            .unwrap()
+            .contains("@dataclass\nclass UserRecord:"));
+    }
+
+    #[test]
+    fn test_render_attrs_and_msgspec_models() {
+        let mut schema = users_schema();
+        schema.tables[0].columns[1].data_type = DataType::Varchar(Some(255));
+
+        let config = CodeGenConfig::new(PathBuf::from("out/database"))
+            .with_python_models(PythonModels::Attrs);
+        let files = PythonGenerator::new().render(&schema, &config).unwrap();
+        let users = files.get("database/users.py").unwrap();
+        assert!(users.contains("import attrs\n"));
+        assert!(users.contains("    _MaxLength,\n"));
+        assert!(users.contains("@attrs.define\nclass UserRecord:"));
+        assert!(users.contains(
+            "    email: str | _Unset = attrs.field(default=UNSET, validator=_MaxLength(255))\n"
+        ));
+        assert!(files
+            .get("database/_types.py")
+            .unwrap()
+            .contains("class _MaxLength:"));
+
+        let config = config.with_python_models(PythonModels::Msgspec);
+        let files = PythonGenerator::new().render(&schema, &config).unwrap();
+        let users = files.get("database/users.py").unwrap();
+        assert!(users.contains("from msgspec import Meta\nfrom msgspec import Struct\n"));
+        assert!(users.contains("from typing import Annotated\n"));
+        assert!(users.contains("class UserRecord(Struct):"));
+        assert!(
+            users.contains("    email: Annotated[str, Meta(max_length=255)] | _Unset = UNSET\n")
+        );
+        assert!(files
+            .get("database/_types.py")
+            .unwrap()
+            .contains("UNSET: _Unset = msgspec.UNSET\n"));
+    }
+
+    #[test]
+    fn test_render_record_helpers() {
+        let schema = users_schema();
+        let config = CodeGenConfig::new(PathBuf::from("out/database"));
+        let files = PythonGenerator::new().render(&schema, &config).unwrap();
+
+        let users = files.get("database/users.py").unwrap();
+        assert!(users.contains("@dataclass\nclass UserRecord:"));
+        assert!(users.contains("    def from_row(cls, row: Sequence[Any]) -> \"UserRecord\":\n"));
+        assert!(users.contains("            \"email\": _json_value(self.email),\n"));
+        assert!(users.contains("return UserRecord.from_row(row)"));
+        assert!(!users.contains("UserRecord(*row)"));
+
+        let config = config.with_frozen_records(true);
+        let files = PythonGenerator::new().render(&schema, &config).unwrap();
+        let users = files.get("database/users.py").unwrap();
+        assert!(users.contains("@dataclass(frozen=True, slots=True)\nclass UserRecord:"));
+        // Params are built up by callers, so only records are frozen
+        assert!(users.contains("@dataclass\nclass UserInsertParams:"));
+    }
+
+    #[test]
+    fn test_render_psycopg2_driver() {
+        let config = CodeGenConfig::new(PathBuf::from("out/database"))
+            .with_python_driver(PythonDriver::Psycopg2);
+        let files = PythonGenerator::new()
+            .render(&users_schema(), &config)
+            .unwrap();
+
+        for (path, code) in files.iter() {
+            assert!(
+                !code.contains("from psycopg ") && !code.contains("import psycopg\n"),
+                "{} imports psycopg 3",
+                path.display()
+            );
+        }
+
+        let users = files.get("database/users.py").unwrap();
+        assert!(users.contains("from psycopg2 import sql\n"));
+        assert!(users.contains("    _execute_values,\n"));
+        assert!(users.contains("\"INSERT INTO users (email) VALUES %s\""));
+        assert!(!users.contains("cursor.copy("));
+
+        let types = files.get("database/_types.py").unwrap();
+        assert!(types.contains("from psycopg2.extras import execute_values\n"));
+        assert!(types.contains("cursor = conn.cursor(cursor_factory=Cursor)"));
+
+        let connection = files.get("database/_connection.py").unwrap();
+        assert!(connection.contains("register_uuid(conn_or_curs=conn)"));
+        assert!(connection.contains("conn = psycopg2.connect(conninfo, **kwargs)"));
+    }
+
     #[test]
     fn test_to_pascal_case() {
         assert_eq!(to_pascal_case("user"), "User");
@@ -0,0 +1,612 @@
+//! pytest scaffolding for generated Python code
+//!
+//! Renders a `tests/` package next to the generated code with a rolled-back
+//! connection fixture, test data factories per table and smoke tests that call every
+//! generated function against a real database.
+
+use std::collections::BTreeSet;
+use std::path::Path;
+
+use tracing::debug;
+
+use super::{driver_name, sample_value, soft_delete_column, upsert_targets, version_column};
+use super::{PythonGenerator, SampleValue, VersionColumn};
+use crate::codegen::{CodeGenConfig, FunctionStyle, GeneratedFiles};
+use crate::error::SqliftError;
+use crate::schema::{Column, DataType, ForeignKey, Schema, Table};
+
+impl PythonGenerator {
+    /// Render `tests/` for the generated code, importable as `module`
+    pub(super) fn render_tests(
+        &self,
+        schema: &Schema,
+        config: &CodeGenConfig,
+        module: &str,
+        dir: &Path,
+        files: &mut GeneratedFiles,
+    ) -> Result<(), SqliftError> {
+        let driver = driver_name(config.python_driver);
+        let function_style = match config.function_style {
+            FunctionStyle::Standalone => "standalone",
+            FunctionStyle::Class => "class",
+        };
+
+        files.insert(
+            dir.join("__init__.py"),
+            "\"\"\"Generated smoke tests.\"\"\"\n".to_string(),
+        );
+        files.insert(
+            dir.join("conftest.py"),
+            self.render_tests_template(
+                "tests_conftest",
+                "conftest",
+                minijinja::context! {
+                    driver => driver,
+                    module => module,
+                },
+            )?,
+        );
+        files.insert(
+            dir.join("factories.py"),
+            self.render_factories(schema, config, module)?,
+        );
+
+        for table in &schema.tables {
+            let ctx = self.build_table_context(table, schema, config)?;
+            let version = version_column(table, config)?;
+            let soft_delete = soft_delete_column(table, config)?;
+            let update_column = update_column(table, soft_delete, version.as_ref());
+            let has_upsert = !table.primary_key.is_empty()
+                && !table.has_auto_generated_pk()
+                && !table.non_pk_columns().is_empty();
+
+            let ctx = minijinja::context! {
+                module => module,
+                function_style => function_style,
+                repository_name => format!("{}Repository", table.singular_class_name()),
+                pk_suffix => table.primary_key.join("_and_"),
+                filter_columns => filter_columns(table),
+                update_column => update_column,
+                has_upsert => has_upsert,
+                module_imports => test_imports(table, config, update_column, soft_delete, has_upsert),
+                ..ctx
+            };
+
+            files.insert(
+                dir.join(format!("test_{}.py", table.name)),
+                self.render_tests_template("tests_table", &table.name, ctx)?,
+            );
+            debug!(table = ?table.name, "Generated test file");
+        }
+
+        Ok(())
+    }
+
+    /// Render factories.py with fake values and insert parameter factories per table
+    fn render_factories(
+        &self,
+        schema: &Schema,
+        config: &CodeGenConfig,
+        module: &str,
+    ) -> Result<String, SqliftError> {
+        let mut imports = BTreeSet::from([
+            "from typing import Any".to_string(),
+            "from uuid import uuid4".to_string(),
+        ]);
+        let mut module_imports = BTreeSet::new();
+
+        let mut tables_ctx = Vec::new();
+        for table in &schema.tables {
+            let soft_delete = soft_delete_column(table, config)?;
+            let insert_columns = table.insert_columns();
+            let in_foreign_key = |name: &str| {
+                table
+                    .foreign_keys
+                    .iter()
+                    .any(|fk| fk.columns.iter().any(|col| col == name))
+            };
+
+            let values: Vec<_> = insert_columns
+                .iter()
+                .filter(|col| !in_foreign_key(&col.name))
+                .map(|col| {
+                    // Soft-deleted rows would be hidden from every read
+                    let value = if Some(col.name.as_str()) == soft_delete {
+                        "None".to_string()
+                    } else {
+                        sample_value(
+                            &col.data_type,
+                            SampleValue::Fake,
+                            schema,
+                            &mut imports,
+                            &mut module_imports,
+                        )
+                    };
+                    minijinja::context! { name => &col.name, value => value }
+                })
+                .collect();
+            let (optional_fk_columns, required_fk_columns): (Vec<&Column>, Vec<&Column>) =
+                insert_columns
+                    .iter()
+                    .filter(|col| in_foreign_key(&col.name))
+                    .partition(|col| col.is_nullable);
+
+            let parents: Vec<_> = table
+                .foreign_keys
+                .iter()
+                .filter(|fk| follows_foreign_key(table, fk, schema))
+                .map(|fk| {
+                    let parent = schema
+                        .tables
+                        .iter()
+                        .find(|t| t.name == fk.referenced_table)
+                        .expect("followed foreign keys reference generated tables");
+                    minijinja::context! {
+                        singular_name => parent.singular_name(),
+                        columns => fk
+                            .columns
+                            .iter()
+                            .zip(&fk.referenced_columns)
+                            .map(|(name, referenced)| minijinja::context! {
+                                name => name,
+                                referenced => referenced,
+                            })
+                            .collect::<Vec<_>>(),
+                    }
+                })
+                .collect();
+
+            let class_name = table.singular_class_name();
+            module_imports.insert(format!("{}Record", class_name));
+            if !insert_columns.is_empty() {
+                module_imports.insert(format!("{}InsertParams", class_name));
+            }
+            match config.function_style {
+                FunctionStyle::Standalone => {
+                    module_imports.insert(format!("insert_{}", table.singular_name()));
+                }
+                FunctionStyle::Class => {
+                    module_imports.insert(format!("{}Repository", class_name));
+                }
+            }
+
+            tables_ctx.push(minijinja::context! {
+                table_name => &table.name,
+                singular_name => table.singular_name(),
+                record_name => format!("{}Record", class_name),
+                insert_params_name => format!("{}InsertParams", class_name),
+                repository_name => format!("{}Repository", class_name),
+                insert_columns => insert_columns.iter().map(|col| &col.name).collect::<Vec<_>>(),
+                values => values,
+                optional_fk_columns => optional_fk_columns
+                    .iter()
+                    .map(|col| &col.name)
+                    .collect::<Vec<_>>(),
+                required_fk_columns => required_fk_columns
+                    .iter()
+                    .map(|col| &col.name)
+                    .collect::<Vec<_>>(),
+                parents => parents,
+            });
+        }
+
+        let ctx = minijinja::context! {
+            driver => driver_name(config.python_driver),
+            function_style => match config.function_style {
+                FunctionStyle::Standalone => "standalone",
+                FunctionStyle::Class => "class",
+            },
+            module => module,
+            imports => imports,
+            module_imports => module_imports,
+            tables => tables_ctx,
+        };
+
+        self.render_tests_template("tests_factories", "factories", ctx)
+    }
+
+    fn render_tests_template(
+        &self,
+        name: &str,
+        table: &str,
+        ctx: minijinja::Value,
+    ) -> Result<String, SqliftError> {
+        let template = self
+            .env
+            .get_template(name)
+            .map_err(|e| SqliftError::CodeGen {
+                table: table.to_string(),
+                message: format!("Template error: {}", e),
+            })?;
+
+        template.render(ctx).map_err(|e| SqliftError::CodeGen {
+            table: table.to_string(),
+            message: format!("Render error: {}", e),
+        })
+    }
+}
+
+/// Whether a row can only be inserted once a row it references exists
+///
+/// That is the case when any foreign key column is NOT NULL and every one is
+/// inserted explicitly.
+fn requires_parent(table: &Table, fk: &ForeignKey) -> bool {
+    let insert_columns = table.insert_columns();
+    let columns: Vec<_> = fk
+        .columns
+        .iter()
+        .map(|name| insert_columns.iter().find(|col| col.name == *name))
+        .collect();
+    columns.iter().all(Option::is_some) && columns.iter().flatten().any(|col| !col.is_nullable)
+}
+
+/// Whether build_* factories insert the parent row for a foreign key
+///
+/// Keys that lead back to the table through other required keys are not
+/// followed so building a row can't recurse forever.
+fn follows_foreign_key(table: &Table, fk: &ForeignKey, schema: &Schema) -> bool {
+    requires_parent(table, fk)
+        && schema.tables.iter().any(|t| t.name == fk.referenced_table)
+        && !references(schema, &fk.referenced_table, &table.name, &mut Vec::new())
+}
+
+/// Whether `from` requires a `target` row through a chain of foreign keys
+fn references<'a>(
+    schema: &'a Schema,
+    from: &'a str,
+    target: &str,
+    seen: &mut Vec<&'a str>,
+) -> bool {
+    if from == target {
+        return true;
+    }
+    if seen.contains(&from) {
+        return false;
+    }
+    seen.push(from);
+
+    schema
+        .tables
+        .iter()
+        .filter(|t| t.name == from)
+        .flat_map(|t| t.foreign_keys.iter().map(move |fk| (t, fk)))
+        .filter(|(t, fk)| requires_parent(t, fk))
+        .any(|(_, fk)| references(schema, &fk.referenced_table, target, seen))
+}
+
+/// Column changed by the update tests, if the table can be updated
+fn update_column<'a>(
+    table: &'a Table,
+    soft_delete: Option<&str>,
+    version: Option<&VersionColumn>,
+) -> Option<&'a str> {
+    if table.primary_key.is_empty() {
+        return None;
+    }
+
+    table
+        .insert_columns()
+        .into_iter()
+        .filter(|col| !table.primary_key.contains(&col.name))
+        .filter(|col| Some(col.name.as_str()) != soft_delete)
+        .filter(|col| !matches!(version, Some(VersionColumn::Integer(name)) if *name == col.name))
+        .filter(|col| {
+            !table
+                .foreign_keys
+                .iter()
+                .any(|fk| fk.columns.contains(&col.name))
+        })
+        .map(|col| col.name.as_str())
+        .next()
+}
+
+/// Columns the read tests filter on to find the inserted row
+///
+/// The primary key when there is one, otherwise the first column that can be
+/// compared with `=`.
+fn filter_columns(table: &Table) -> Vec<&str> {
+    if !table.primary_key.is_empty() {
+        return table.primary_key.iter().map(String::as_str).collect();
+    }
+
+    table
+        .columns
+        .iter()
+        .filter(|col| {
+            !matches!(
+                col.data_type,
+                DataType::Json | DataType::JsonBinary | DataType::Array(_) | DataType::Binary
+            )
+        })
+        .map(|col| col.name.as_str())
+        .take(1)
+        .collect()
+}
+
+/// Names a test module imports from the generated code
+fn test_imports(
+    table: &Table,
+    config: &CodeGenConfig,
+    update_column: Option<&str>,
+    soft_delete: Option<&str>,
+    has_upsert: bool,
+) -> Vec<String> {
+    let singular = table.singular_name();
+    let class_name = table.singular_class_name();
+    let plural = &table.name;
+    let has_pk = !table.primary_key.is_empty();
+    let targets = upsert_targets(table);
+
+    let mut names = vec!["ColumnFilter".to_string(), format!("{}Filter", class_name)];
+    if update_column.is_some() {
+        names.push(format!("{}UpdateParams", class_name));
+    }
+    if !targets.is_empty() {
+        names.push(format!("{}UpsertParams", class_name));
+    }
+
+    if config.function_style == FunctionStyle::Class {
+        names.push(format!("{}Repository", class_name));
+        return names;
+    }
+
+    let pk_suffix = table.primary_key.join("_and_");
+    if has_pk {
+        names.push(format!("get_{}_by_{}", singular, pk_suffix));
+        names.push(format!("exists_{}_by_{}", singular, pk_suffix));
+    }
+    names.push(format!("find_{}", plural));
+    names.push(format!("count_{}", plural));
+    if has_pk {
+        names.push(format!("get_{}_page", plural));
+    }
+    names.push(format!("get_all_{}", plural));
+    if update_column.is_some() {
+        names.push(format!("update_{}", singular));
+        names.push(format!("bulk_update_{}", plural));
+        names.push(format!("batch_update_{}", plural));
+    }
+    if has_upsert {
+        names.push(format!("upsert_{}", singular));
+    }
+    for (suffix, _) in &targets {
+        names.push(format!("bulk_upsert_{}{}", plural, suffix));
+        names.push(format!("bulk_upsert_{}{}_returning", plural, suffix));
+    }
+    if has_pk {
+        names.push(format!("delete_{}", singular));
+        if soft_delete.is_some() {
+            names.push(format!("restore_{}", singular));
+            names.push(format!("hard_delete_{}", singular));
+        }
+        names.push(format!("bulk_delete_{}", plural));
+    }
+    if !table.insert_columns().is_empty() {
+        names.push(format!("bulk_insert_{}", plural));
+        names.push(format!("bulk_insert_{}_returning", plural));
+        names.push(format!("copy_{}", plural));
+    }
+
+    names
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::codegen::CodeGenerator;
+    use crate::schema::EnumType;
+    use std::path::PathBuf;
+
+    fn column(name: &str, data_type: DataType, is_nullable: bool) -> Column {
+        Column {
+            name: name.to_string(),
+            data_type,
+            is_nullable,
+            has_default: false,
+            is_auto_generated: false,
+        }
+    }
+
+    fn table(name: &str, columns: Vec<Column>, foreign_keys: Vec<ForeignKey>) -> Table {
+        Table {
+            name: name.to_string(),
+            columns,
+            primary_key: vec!["id".to_string()],
+            unique_constraints: vec![],
+            foreign_keys,
+        }
+    }
+
+    fn foreign_key(column: &str, referenced_table: &str) -> ForeignKey {
+        ForeignKey {
+            name: format!("{}_fkey", column),
+            columns: vec![column.to_string()],
+            referenced_table: referenced_table.to_string(),
+            referenced_columns: vec!["id".to_string()],
+        }
+    }
+
+    #[test]
+    fn test_follows_foreign_key() {
+        let schema = Schema {
+            name: "public".to_string(),
+            tables: vec![
+                table(
+                    "users",
+                    vec![
+                        column("id", DataType::Integer, false),
+                        column("manager_id", DataType::Integer, false),
+                    ],
+                    vec![foreign_key("manager_id", "users")],
+                ),
+                table(
+                    "orders",
+                    vec![
+                        column("id", DataType::Integer, false),
+                        column("user_id", DataType::Integer, false),
+                        column("region_id", DataType::Integer, false),
+                        column("referrer_id", DataType::Integer, true),
+                    ],
+                    vec![
+                        foreign_key("user_id", "users"),
+                        foreign_key("region_id", "regions"),
+                        foreign_key("referrer_id", "users"),
+                    ],
+                ),
+            ],
+            enums: vec![],
+        };
+
+        let users = &schema.tables[0];
+        let orders = &schema.tables[1];
+        // Self references would recurse forever
+        assert!(!follows_foreign_key(users, &users.foreign_keys[0], &schema));
+        assert!(follows_foreign_key(
+            orders,
+            &orders.foreign_keys[0],
+            &schema
+        ));
+        // regions isn't generated
+        assert!(!follows_foreign_key(
+            orders,
+            &orders.foreign_keys[1],
+            &schema
+        ));
+        // Nullable keys are left as None
+        assert!(!follows_foreign_key(
+            orders,
+            &orders.foreign_keys[2],
+            &schema
+        ));
+    }
+
+    #[test]
+    fn test_render_tests() {
+        let schema = Schema {
+            name: "public".to_string(),
+            tables: vec![
+                table(
+                    "users",
+                    vec![
+                        Column {
+                            has_default: true,
+                            is_auto_generated: true,
+                            ..column("id", DataType::Integer, false)
+                        },
+                        column("email", DataType::Varchar(Some(255)), false),
+                    ],
+                    vec![],
+                ),
+                table(
+                    "orders",
+                    vec![
+                        column("id", DataType::Uuid, false),
+                        column("user_id", DataType::Integer, false),
+                        column("total", DataType::Numeric, false),
+                    ],
+                    vec![foreign_key("user_id", "users")],
+                ),
+            ],
+            enums: vec![],
+        };
+
+        let config = CodeGenConfig::new(PathBuf::from("out/database")).with_tests(true);
+        let files = PythonGenerator::new().render(&schema, &config).unwrap();
+
+        let conftest = files.get("tests/conftest.py").unwrap();
+        assert!(conftest.contains("from database import connect"));
+        assert!(conftest.contains("os.environ.get(\"TEST_DATABASE_URL\")"));
+
+        let factories = files.get("tests/factories.py").unwrap();
+        assert!(factories.contains("\"email\": fake_str(255),"));
+        assert!(
+            factories.contains("missing = [name for name in [\"user_id\"] if name not in values]")
+        );
+        assert!(factories.contains(
+            "    if \"user_id\" not in overrides:\n        overrides[\"user_id\"] = create_user(conn).id\n"
+        ));
+        assert!(
+            factories.contains("    return insert_order(\n        conn,\n        id=params.id,\n")
+        );
+
+        let tests = files.get("tests/test_orders.py").unwrap();
+        assert!(tests.contains("record = create_order(conn, **values)"));
+        assert!(tests.contains("assert get_order_by_id(conn, id=record.id) == record"));
+        // Foreign key columns are never updated to random values
+        assert!(tests.contains("value = order_values()[\"total\"]"));
+        assert!(tests.contains("assert bulk_delete_orders(conn, ids=[record.id]) == 1"));
+
+        let config = config.with_function_style(FunctionStyle::Class);
+        let files = PythonGenerator::new().render(&schema, &config).unwrap();
+        let factories = files.get("tests/factories.py").unwrap();
+        assert!(
+            factories.contains("    return OrderRepository(conn).insert(\n        id=params.id,\n")
+        );
+        let tests = files.get("tests/test_orders.py").unwrap();
+        assert!(tests.contains("    repo = OrderRepository(conn)\n    values = order_values()"));
+        assert!(tests.contains("assert repo.get_by_id(id=record.id) == record"));
+    }
+
+    #[test]
+    fn test_render_factories() {
+        let schema = Schema {
+            name: "public".to_string(),
+            tables: vec![
+                table(
+                    "users",
+                    vec![
+                        Column {
+                            has_default: true,
+                            is_auto_generated: true,
+                            ..column("id", DataType::Integer, false)
+                        },
+                        column("email", DataType::Varchar(Some(8)), false),
+                    ],
+                    vec![],
+                ),
+                table(
+                    "orders",
+                    vec![
+                        column("id", DataType::Uuid, false),
+                        column("user_id", DataType::Integer, false),
+                        column("status", DataType::Enum("order_status".to_string()), false),
+                    ],
+                    vec![foreign_key("user_id", "users")],
+                ),
+            ],
+            enums: vec![EnumType {
+                name: "order_status".to_string(),
+                values: vec!["pending".to_string(), "shipped".to_string()],
+            }],
+        };
+
+        let config = CodeGenConfig::new(PathBuf::from("out/database")).with_tests(true);
+        let files = PythonGenerator::new().render(&schema, &config).unwrap();
+        let factories = files.get("tests/factories.py").unwrap();
+
+        // Strings are cut to the varchar length
+        assert!(factories.contains("        \"email\": fake_str(8),\n"));
+        assert!(factories.contains("    return value[:max_length]\n"));
+
+        // Enums use their first value
+        assert!(factories.contains("        \"status\": OrderStatus.PENDING,\n"));
+        assert!(factories.contains("    OrderStatus,\n"));
+
+        // The parent row is created before the child's params are built,
+        // and those before the child row is inserted
+        let build = &factories[factories.find("def build_order(").unwrap()..];
+        let build = &build[..build.find("\n\n\n").unwrap()];
+        let parent = build.find("create_user(conn)").unwrap();
+        assert!(
+            parent
+                < build
+                    .find("return make_order_insert_params(**overrides)")
+                    .unwrap()
+        );
+        let create = &factories[factories.find("def create_order(").unwrap()..];
+        assert!(
+            create.find("build_order(conn, **overrides)").unwrap()
+                < create.find("insert_order(").unwrap()
+        );
+    }
+}
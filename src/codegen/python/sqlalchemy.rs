@@ -0,0 +1,640 @@
+//! SQLAlchemy Core code generator
+//!
+//! Generates SQLAlchemy `Table` definitions with typed select and insert
+//! helpers, as an alternative to the psycopg data access functions.
+
+use std::collections::BTreeSet;
+
+use minijinja::Environment;
+use tracing::{debug, info, warn};
+
+use super::{build_column_context, collect_imports, collect_table_imports, python_type};
+use crate::codegen::files::split_output_path;
+use crate::codegen::{
+    CodeGenConfig, CodeGenerator, GeneratedFiles, OutputMode, PythonModels, MANIFEST_FILE,
+};
+use crate::error::SqliftError;
+use crate::schema::{to_pascal_case, DataType, EnumType, Schema, Table};
+
+/// SQLAlchemy Core code generator
+pub struct SqlAlchemyGenerator {
+    env: Environment<'static>,
+}
+
+impl SqlAlchemyGenerator {
+    pub fn new() -> Self {
+        let mut env = Environment::new();
+
+        // Register templates
+        env.add_template("table", include_str!("templates/sqlalchemy/table.py.jinja"))
+            .expect("Failed to load table template");
+        env.add_template(
+            "module",
+            include_str!("templates/sqlalchemy/module.py.jinja"),
+        )
+        .expect("Failed to load module template");
+        env.add_template(
+            "metadata",
+            include_str!("templates/sqlalchemy/metadata.py.jinja"),
+        )
+        .expect("Failed to load metadata template");
+        env.add_template("init", include_str!("templates/sqlalchemy/init.py.jinja"))
+            .expect("Failed to load init template");
+        env.add_template("flat", include_str!("templates/sqlalchemy/flat.py.jinja"))
+            .expect("Failed to load flat template");
+        env.add_template("enum", include_str!("templates/enum.py.jinja"))
+            .expect("Failed to load enum template");
+
+        Self { env }
+    }
+}
+
+impl Default for SqlAlchemyGenerator {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl CodeGenerator for SqlAlchemyGenerator {
+    fn render(
+        &self,
+        schema: &Schema,
+        config: &CodeGenConfig,
+    ) -> Result<GeneratedFiles, SqliftError> {
+        info!(
+            output = ?config.output_path,
+            mode = ?config.output_mode,
+            "Generating SQLAlchemy code"
+        );
+
+        let files = match config.output_mode {
+            OutputMode::Library => self.generate_library(schema, config)?,
+            OutputMode::Flat => self.generate_flat(schema, config)?,
+        };
+
+        info!(
+            files = files.len(),
+            tables = schema.tables.len(),
+            enums = schema.enums.len(),
+            "SQLAlchemy code generation complete"
+        );
+
+        Ok(files)
+    }
+}
+
+/// Names a generated module imports from SQLAlchemy
+#[derive(Debug, Default)]
+struct SqlAlchemyImports {
+    /// Names imported from the `sqlalchemy` package
+    core: BTreeSet<&'static str>,
+    /// Names imported from `sqlalchemy.types`
+    types: BTreeSet<&'static str>,
+    /// Whether the PostgreSQL dialect types are used
+    postgresql: bool,
+    /// Shared enum type objects
+    enums: BTreeSet<String>,
+}
+
+impl SqlAlchemyImports {
+    fn extend(&mut self, other: SqlAlchemyImports) {
+        self.core.extend(other.core);
+        self.types.extend(other.types);
+        self.postgresql |= other.postgresql;
+        self.enums.extend(other.enums);
+    }
+}
+
+impl SqlAlchemyGenerator {
+    /// Generate library mode output
+    fn generate_library(
+        &self,
+        schema: &Schema,
+        config: &CodeGenConfig,
+    ) -> Result<GeneratedFiles, SqliftError> {
+        let (root, package) = split_output_path(&config.output_path);
+        let mut files = GeneratedFiles::new(root).with_manifest(package.join(MANIFEST_FILE));
+
+        if !schema.enums.is_empty() {
+            files.insert(package.join("enums.py"), self.render_enums(&schema.enums)?);
+            debug!("Generated enums file");
+        }
+
+        files.insert(
+            package.join("_metadata.py"),
+            self.render_template(
+                "metadata",
+                "_metadata",
+                minijinja::context! { enums => enums_context(&schema.enums) },
+            )?,
+        );
+        debug!("Generated _metadata.py");
+
+        for table in &schema.tables {
+            let code = self.render_table(table, schema, config)?;
+            files.insert(package.join(format!("{}.py", table.name)), code);
+            debug!(table = ?table.name, "Generated table file")
+        }
+
+        files.insert(
+            package.join("__init__.py"),
+            self.render_template("init", "__init__", init_context(schema))?,
+        );
+        debug!("Generated __init__.py");
+
+        Ok(files)
+    }
+
+    /// Generate flat mode output (single file)
+    fn generate_flat(
+        &self,
+        schema: &Schema,
+        config: &CodeGenConfig,
+    ) -> Result<GeneratedFiles, SqliftError> {
+        let output_path = &config.output_path;
+
+        let final_path = if output_path.extension().is_some_and(|ext| ext == "py") {
+            output_path.clone()
+        } else {
+            output_path.with_extension("py")
+        };
+
+        let (root, file_name) = split_output_path(&final_path);
+        let mut files = GeneratedFiles::new(root);
+        files.insert(file_name, self.render_flat(schema, config)?);
+        debug!(path = ?final_path, "Generated flat SQLAlchemy file");
+
+        Ok(files)
+    }
+
+    /// Render enums file, shared with the psycopg output
+    fn render_enums(&self, enums: &[EnumType]) -> Result<String, SqliftError> {
+        self.render_template(
+            "enum",
+            "enums",
+            minijinja::context! { enums => enums_context(enums) },
+        )
+    }
+
+    /// Render a single table module
+    fn render_table(
+        &self,
+        table: &Table,
+        schema: &Schema,
+        config: &CodeGenConfig,
+    ) -> Result<String, SqliftError> {
+        let (body, sqlalchemy) = self.render_table_body(table, schema, config)?;
+
+        let mut imports = collect_table_imports(table, schema, PythonModels::Dataclass);
+        imports.retain(|imp| imp != "from typing import Any");
+
+        let ctx = minijinja::context! {
+            table_name => &table.name,
+            imports => imports,
+            sqlalchemy_imports => sqlalchemy.core,
+            type_imports => sqlalchemy.types,
+            uses_postgresql => sqlalchemy.postgresql,
+            enum_types => sqlalchemy.enums,
+            body => body,
+        };
+
+        self.render_template("module", &table.name, ctx)
+    }
+
+    /// Render flat file with all tables
+    fn render_flat(&self, schema: &Schema, config: &CodeGenConfig) -> Result<String, SqliftError> {
+        let mut sqlalchemy = SqlAlchemyImports::default();
+        sqlalchemy.core.insert("MetaData");
+        if !schema.enums.is_empty() {
+            sqlalchemy.postgresql = true;
+        }
+
+        let mut tables_ctx = Vec::new();
+        for table in &schema.tables {
+            let (body, table_imports) = self.render_table_body(table, schema, config)?;
+            sqlalchemy.extend(table_imports);
+            tables_ctx.push(minijinja::context! { body => body });
+        }
+
+        let mut imports = collect_imports(schema, PythonModels::Dataclass);
+        // Enums are defined in the flat file itself
+        imports.retain(|imp| {
+            !imp.starts_with("from .enums import") && imp != "from typing import Any"
+        });
+
+        let ctx = minijinja::context! {
+            schema_name => &schema.name,
+            imports => imports,
+            sqlalchemy_imports => sqlalchemy.core,
+            type_imports => sqlalchemy.types,
+            uses_postgresql => sqlalchemy.postgresql,
+            enums => enums_context(&schema.enums),
+            tables => tables_ctx,
+        };
+
+        self.render_template("flat", "flat", ctx)
+    }
+
+    /// Render the table definition, records and helpers shared by both output modes
+    fn render_table_body(
+        &self,
+        table: &Table,
+        schema: &Schema,
+        config: &CodeGenConfig,
+    ) -> Result<(String, SqlAlchemyImports), SqliftError> {
+        let mut sqlalchemy = SqlAlchemyImports::default();
+        sqlalchemy.core.extend([
+            "Column",
+            "Connection",
+            "Select",
+            "Table",
+            "insert",
+            "select",
+        ]);
+
+        let columns_ctx: Vec<_> = table
+            .columns
+            .iter()
+            .map(|col| {
+                if col.has_default {
+                    sqlalchemy.core.insert("FetchedValue");
+                }
+                minijinja::context! {
+                    sqlalchemy_type => sqlalchemy_type(&col.data_type, schema, &mut sqlalchemy),
+                    ..build_column_context(col, schema)
+                }
+            })
+            .collect();
+
+        if !table.primary_key.is_empty() {
+            sqlalchemy.core.insert("PrimaryKeyConstraint");
+        }
+        if !table.unique_constraints.is_empty() {
+            sqlalchemy.core.insert("UniqueConstraint");
+        }
+
+        // Constraints referencing tables outside the schema can't be resolved
+        // against the generated metadata
+        let foreign_keys: Vec<_> = table
+            .foreign_keys
+            .iter()
+            .filter(|fk| {
+                let known = schema
+                    .tables
+                    .iter()
+                    .any(|other| other.name == fk.referenced_table);
+                if !known {
+                    warn!(
+                        table = ?table.name,
+                        constraint = ?fk.name,
+                        referenced_table = ?fk.referenced_table,
+                        "Skipping foreign key to a table that is not generated"
+                    );
+                }
+                known
+            })
+            .collect();
+        if !foreign_keys.is_empty() {
+            sqlalchemy.core.insert("ForeignKeyConstraint");
+        }
+
+        let row_type = format!(
+            "tuple[{}]",
+            table
+                .columns
+                .iter()
+                .map(|col| python_type(&col.data_type, col.is_nullable, schema))
+                .collect::<Vec<_>>()
+                .join(", ")
+        );
+
+        let ctx = minijinja::context! {
+            frozen_records => config.frozen_records,
+            table_name => &table.name,
+            table_var => table_var(table),
+            singular_name => table.singular_name(),
+            singular_class_name => table.singular_class_name(),
+            record_name => format!("{}Record", table.singular_class_name()),
+            row_type => row_type,
+            columns => columns_ctx,
+            primary_key => &table.primary_key,
+            pk_suffix => table.primary_key.join("_and_"),
+            pk_columns => table
+                .primary_key_columns()
+                .iter()
+                .map(|col| build_column_context(col, schema))
+                .collect::<Vec<_>>(),
+            insert_columns => table
+                .insert_columns()
+                .iter()
+                .map(|col| build_column_context(col, schema))
+                .collect::<Vec<_>>(),
+            unique_constraints => &table.unique_constraints,
+            foreign_keys => foreign_keys,
+        };
+
+        let body = self.render_template("table", &table.name, ctx)?;
+        Ok((body, sqlalchemy))
+    }
+
+    fn render_template(
+        &self,
+        name: &str,
+        table: &str,
+        ctx: minijinja::Value,
+    ) -> Result<String, SqliftError> {
+        let template = self
+            .env
+            .get_template(name)
+            .map_err(|e| SqliftError::CodeGen {
+                table: table.to_string(),
+                message: format!("Template error: {}", e),
+            })?;
+
+        template.render(ctx).map_err(|e| SqliftError::CodeGen {
+            table: table.to_string(),
+            message: format!("Render error: {}", e),
+        })
+    }
+}
+
+/// Name of the module-level `Table` object for a table
+fn table_var(table: &Table) -> String {
+    format!("{}_table", table.name)
+}
+
+/// Name of the shared `postgresql.ENUM` object for a database enum
+fn enum_type_var(name: &str) -> String {
+    format!("{}_type", name)
+}
+
+fn enums_context(enums: &[EnumType]) -> minijinja::Value {
+    minijinja::Value::from_serialize(
+        enums
+            .iter()
+            .map(|e| {
+                minijinja::context! {
+                    name => to_pascal_case(&e.name),
+                    db_name => &e.name,
+                    type_name => enum_type_var(&e.name),
+                    values => &e.values,
+                }
+            })
+            .collect::<Vec<_>>(),
+    )
+}
+
+fn init_context(schema: &Schema) -> minijinja::Value {
+    let tables_ctx: Vec<_> = schema
+        .tables
+        .iter()
+        .map(|t| {
+            minijinja::context! {
+                module_name => &t.name,
+                table_name => &t.name,
+                table_var => table_var(t),
+                singular_name => t.singular_name(),
+                record_name => format!("{}Record", t.singular_class_name()),
+                insert_params_name => format!("{}InsertParams", t.singular_class_name()),
+                has_pk => !t.primary_key.is_empty(),
+                has_insert_params => !t.insert_columns().is_empty(),
+                pk_suffix => t.primary_key.join("_and_"),
+            }
+        })
+        .collect();
+
+    minijinja::context! {
+        tables => tables_ctx,
+        enums => enums_context(&schema.enums),
+    }
+}
+
+/// SQLAlchemy type expression for a column type
+///
+/// Generic types come from `sqlalchemy.types`; arrays, JSON, UUIDs and enums
+/// use the PostgreSQL dialect types so DDL emitted from the metadata matches
+/// the database.
+fn sqlalchemy_type(
+    data_type: &DataType,
+    schema: &Schema,
+    imports: &mut SqlAlchemyImports,
+) -> String {
+    let generic = |name: &'static str, imports: &mut SqlAlchemyImports| {
+        imports.types.insert(name);
+        name.to_string()
+    };
+
+    match data_type {
+        DataType::SmallInt => generic("SmallInteger", imports),
+        DataType::Integer => generic("Integer", imports),
+        DataType::BigInt => generic("BigInteger", imports),
+        DataType::Boolean => generic("Boolean", imports),
+        DataType::Text => generic("Text", imports),
+        DataType::Varchar(Some(len)) => format!("{}({})", generic("String", imports), len),
+        DataType::Varchar(None) => generic("String", imports),
+        DataType::Char(Some(len)) => format!("{}({})", generic("CHAR", imports), len),
+        DataType::Char(None) => generic("CHAR", imports),
+        DataType::Real => generic("REAL", imports),
+        DataType::DoublePrecision => generic("DOUBLE_PRECISION", imports),
+        DataType::Numeric => generic("Numeric", imports),
+        DataType::Timestamp => generic("DateTime", imports),
+        DataType::TimestampTz => format!("{}(timezone=True)", generic("DateTime", imports)),
+        DataType::Date => generic("Date", imports),
+        DataType::Time => generic("Time", imports),
+        DataType::TimeTz => format!("{}(timezone=True)", generic("Time", imports)),
+        DataType::Binary => generic("LargeBinary", imports),
+        DataType::Uuid => {
+            imports.postgresql = true;
+            "postgresql.UUID".to_string()
+        }
+        DataType::Json => {
+            imports.postgresql = true;
+            "postgresql.JSON".to_string()
+        }
+        DataType::JsonBinary => {
+            imports.postgresql = true;
+            "postgresql.JSONB".to_string()
+        }
+        DataType::Array(inner) => {
+            imports.postgresql = true;
+            format!(
+                "postgresql.ARRAY({})",
+                sqlalchemy_type(inner, schema, imports)
+            )
+        }
+        DataType::Enum(name) if schema.enums.iter().any(|e| &e.name == name) => {
+            let var = enum_type_var(name);
+            imports.enums.insert(var.clone());
+            var
+        }
+        // Unknown enum, read and written as text like its Python type
+        DataType::Enum(_) => generic("Text", imports),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::schema::{Column, ForeignKey, UniqueConstraint};
+    use std::path::PathBuf;
+
+    fn column(name: &str, data_type: DataType, is_nullable: bool) -> Column {
+        Column {
+            name: name.to_string(),
+            data_type,
+            is_nullable,
+            has_default: false,
+            is_auto_generated: false,
+        }
+    }
+
+    fn orders_schema() -> Schema {
+        Schema {
+            name: "public".to_string(),
+            tables: vec![
+                Table {
+                    name: "users".to_string(),
+                    columns: vec![
+                        Column {
+                            has_default: true,
+                            is_auto_generated: true,
+                            ..column("id", DataType::Integer, false)
+                        },
+                        column("email", DataType::Varchar(Some(255)), false),
+                    ],
+                    primary_key: vec!["id".to_string()],
+                    unique_constraints: vec![UniqueConstraint {
+                        name: "users_email_key".to_string(),
+                        columns: vec!["email".to_string()],
+                    }],
+                    foreign_keys: vec![],
+                },
+                Table {
+                    name: "orders".to_string(),
+                    columns: vec![
+                        column("id", DataType::Uuid, false),
+                        column("user_id", DataType::Integer, false),
+                        column("status", DataType::Enum("order_status".to_string()), false),
+                        column("tags", DataType::Array(Box::new(DataType::Text)), true),
+                        column("meta", DataType::JsonBinary, true),
+                    ],
+                    primary_key: vec!["id".to_string()],
+                    unique_constraints: vec![],
+                    foreign_keys: vec![
+                        ForeignKey {
+                            name: "orders_user_id_fkey".to_string(),
+                            columns: vec!["user_id".to_string()],
+                            referenced_table: "users".to_string(),
+                            referenced_columns: vec!["id".to_string()],
+                        },
+                        ForeignKey {
+                            name: "orders_region_id_fkey".to_string(),
+                            columns: vec!["region_id".to_string()],
+                            referenced_table: "regions".to_string(),
+                            referenced_columns: vec!["id".to_string()],
+                        },
+                    ],
+                },
+            ],
+            enums: vec![EnumType {
+                name: "order_status".to_string(),
+                values: vec!["pending".to_string(), "shipped".to_string()],
+            }],
+        }
+    }
+
+    #[test]
+    fn test_sqlalchemy_type() {
+        let schema = orders_schema();
+        let mut imports = SqlAlchemyImports::default();
+
+        assert_eq!(
+            sqlalchemy_type(&DataType::Varchar(Some(255)), &schema, &mut imports),
+            "String(255)"
+        );
+        assert_eq!(
+            sqlalchemy_type(&DataType::TimestampTz, &schema, &mut imports),
+            "DateTime(timezone=True)"
+        );
+        assert_eq!(
+            sqlalchemy_type(
+                &DataType::Array(Box::new(DataType::Enum("order_status".to_string()))),
+                &schema,
+                &mut imports
+            ),
+            "postgresql.ARRAY(order_status_type)"
+        );
+        assert_eq!(
+            sqlalchemy_type(&DataType::Enum("mood".to_string()), &schema, &mut imports),
+            "Text"
+        );
+
+        assert_eq!(
+            imports.types.into_iter().collect::<Vec<_>>(),
+            vec!["DateTime", "String", "Text"]
+        );
+        assert!(imports.postgresql);
+        assert_eq!(
+            imports.enums.into_iter().collect::<Vec<_>>(),
+            vec!["order_status_type"]
+        );
+    }
+
+    #[test]
+    fn test_render_library() {
+        let generator = SqlAlchemyGenerator::new();
+        let config = CodeGenConfig::new(PathBuf::from("out/db"));
+        let files = generator.render(&orders_schema(), &config).unwrap();
+
+        let metadata = files.get("db/_metadata.py").unwrap();
+        assert!(metadata.contains("metadata = MetaData()"));
+        assert!(metadata.contains(
+            "order_status_type = postgresql.ENUM(\n    OrderStatus,\n    name=\"order_status\","
+        ));
+
+        let users = files.get("db/users.py").unwrap();
+        assert!(users
+            .contains("Column(\"id\", Integer, nullable=False, server_default=FetchedValue()),"));
+        assert!(users.contains("Column(\"email\", String(255), nullable=False),"));
+        assert!(users.contains("PrimaryKeyConstraint(\"id\"),"));
+        assert!(users.contains("UniqueConstraint(\"email\", name=\"users_email_key\"),"));
+        assert!(users.contains("def select_users() -> Select[tuple[int, str]]:"));
+        assert!(users.contains("def get_user_by_id("));
+        assert!(users.contains("def bulk_insert_users("));
+
+        let orders = files.get("db/orders.py").unwrap();
+        assert!(orders.contains("from ._metadata import metadata, order_status_type"));
+        assert!(orders.contains("from sqlalchemy.dialects import postgresql"));
+        assert!(orders.contains("Column(\"id\", postgresql.UUID, nullable=False),"));
+        assert!(orders.contains("Column(\"status\", order_status_type, nullable=False),"));
+        assert!(orders.contains("Column(\"tags\", postgresql.ARRAY(Text)),"));
+        assert!(orders.contains("Column(\"meta\", postgresql.JSONB),"));
+        assert!(orders.contains(
+            "ForeignKeyConstraint(\n        [\"user_id\"],\n        [\"users.id\"],\n        name=\"orders_user_id_fkey\",\n    ),"
+        ));
+        // regions isn't part of the generated schema
+        assert!(!orders.contains("orders_region_id_fkey"));
+
+        let init = files.get("db/__init__.py").unwrap();
+        assert!(init.contains("from ._metadata import metadata, order_status_type"));
+        assert!(init.contains("    orders_table,\n    OrderRecord,"));
+    }
+
+    #[test]
+    fn test_render_flat() {
+        let generator = SqlAlchemyGenerator::new();
+        let config = CodeGenConfig::new(PathBuf::from("out/db.py"))
+            .with_output_mode(OutputMode::Flat)
+            .with_frozen_records(true);
+        let files = generator.render(&orders_schema(), &config).unwrap();
+
+        let code = files.get("db.py").unwrap();
+        assert!(code.contains("class OrderStatus(str, Enum):"));
+        assert!(code.contains("metadata = MetaData()"));
+        assert!(code.contains("users_table = Table("));
+        assert!(code.contains("orders_table = Table("));
+        assert!(code.contains("@dataclass(frozen=True, slots=True)\nclass UserRecord:"));
+        assert!(!code.contains("from .enums import"));
+        assert!(!code.contains("from ._metadata import"));
+    }
+}
@@ -0,0 +1,549 @@
+//! Schema diffing
+//!
+//! Compares two schemas (e.g. a committed snapshot and the live database) and
+//! classifies which changes break the generated API.
+
+use std::fmt;
+
+use serde::Serialize;
+
+use crate::prelude::{Column, DataType, EnumType, Schema, Table};
+
+/// A single difference between two schemas
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum Change {
+    TableAdded {
+        table: String,
+    },
+    TableRemoved {
+        table: String,
+    },
+    ColumnAdded {
+        table: String,
+        column: String,
+        data_type: DataType,
+        is_nullable: bool,
+        has_default: bool,
+    },
+    ColumnRemoved {
+        table: String,
+        column: String,
+    },
+    ColumnTypeChanged {
+        table: String,
+        column: String,
+        from: DataType,
+        to: DataType,
+    },
+    ColumnNullabilityChanged {
+        table: String,
+        column: String,
+        from_nullable: bool,
+        to_nullable: bool,
+    },
+    PrimaryKeyChanged {
+        table: String,
+        from: Vec<String>,
+        to: Vec<String>,
+    },
+    EnumAdded {
+        name: String,
+    },
+    EnumRemoved {
+        name: String,
+    },
+    EnumValueAdded {
+        name: String,
+        value: String,
+    },
+    EnumValueRemoved {
+        name: String,
+        value: String,
+    },
+}
+
+impl Change {
+    /// Whether code generated from the old schema breaks against the new one
+    ///
+    /// Removals, primary key changes, incompatible type changes and columns
+    /// becoming required (NOT NULL without a default) are breaking. Additions
+    /// that only widen the API are not.
+    pub fn is_breaking(&self) -> bool {
+        match self {
+            Change::TableAdded { .. } | Change::EnumAdded { .. } => false,
+            Change::EnumValueAdded { .. } => false,
+            Change::TableRemoved { .. }
+            | Change::ColumnRemoved { .. }
+            | Change::PrimaryKeyChanged { .. }
+            | Change::EnumRemoved { .. }
+            | Change::EnumValueRemoved { .. } => true,
+            Change::ColumnAdded {
+                is_nullable,
+                has_default,
+                ..
+            } => !is_nullable && !has_default,
+            Change::ColumnTypeChanged { from, to, .. } => !is_compatible_type(from, to),
+            Change::ColumnNullabilityChanged {
+                from_nullable,
+                to_nullable,
+                ..
+            } => *from_nullable && !to_nullable,
+        }
+    }
+
+    fn symbol(&self) -> char {
+        match self {
+            Change::TableAdded { .. }
+            | Change::ColumnAdded { .. }
+            | Change::EnumAdded { .. }
+            | Change::EnumValueAdded { .. } => '+',
+            Change::TableRemoved { .. }
+            | Change::ColumnRemoved { .. }
+            | Change::EnumRemoved { .. }
+            | Change::EnumValueRemoved { .. } => '-',
+            _ => '~',
+        }
+    }
+}
+
+impl fmt::Display for Change {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Change::TableAdded { table } => write!(f, "table {} added", table),
+            Change::TableRemoved { table } => write!(f, "table {} removed", table),
+            Change::ColumnAdded {
+                table,
+                column,
+                data_type,
+                is_nullable,
+                has_default,
+            } => {
+                write!(f, "column {}.{} added ({}", table, column, data_type)?;
+                if !is_nullable {
+                    write!(f, " NOT NULL")?;
+                }
+                if *has_default {
+                    write!(f, " DEFAULT")?;
+                }
+                write!(f, ")")
+            }
+            Change::ColumnRemoved { table, column } => {
+                write!(f, "column {}.{} removed", table, column)
+            }
+            Change::ColumnTypeChanged {
+                table,
+                column,
+                from,
+                to,
+            } => write!(
+                f,
+                "column {}.{} type changed: {} -> {}",
+                table, column, from, to
+            ),
+            Change::ColumnNullabilityChanged {
+                table,
+                column,
+                to_nullable,
+                ..
+            } => {
+                if *to_nullable {
+                    write!(f, "column {}.{} is now nullable", table, column)
+                } else {
+                    write!(f, "column {}.{} is now NOT NULL", table, column)
+                }
+            }
+            Change::PrimaryKeyChanged { table, from, to } => write!(
+                f,
+                "primary key of {} changed: ({}) -> ({})",
+                table,
+                from.join(", "),
+                to.join(", ")
+            ),
+            Change::EnumAdded { name } => write!(f, "enum {} added", name),
+            Change::EnumRemoved { name } => write!(f, "enum {} removed", name),
+            Change::EnumValueAdded { name, value } => {
+                write!(f, "enum {} value '{}' added", name, value)
+            }
+            Change::EnumValueRemoved { name, value } => {
+                write!(f, "enum {} value '{}' removed", name, value)
+            }
+        }
+    }
+}
+
+/// The full set of differences between two schemas
+#[derive(Debug, Clone, Default)]
+pub struct SchemaDiff {
+    pub changes: Vec<Change>,
+}
+
+#[derive(Serialize)]
+struct JsonDiff<'a> {
+    breaking: bool,
+    changes: Vec<JsonChange<'a>>,
+}
+
+#[derive(Serialize)]
+struct JsonChange<'a> {
+    #[serde(flatten)]
+    change: &'a Change,
+    breaking: bool,
+}
+
+impl SchemaDiff {
+    /// Compute the changes needed to go from `old` to `new`
+    pub fn between(old: &Schema, new: &Schema) -> Self {
+        let mut changes = Vec::new();
+
+        for old_table in &old.tables {
+            match new.tables.iter().find(|t| t.name == old_table.name) {
+                Some(new_table) => diff_tables(old_table, new_table, &mut changes),
+                None => changes.push(Change::TableRemoved {
+                    table: old_table.name.clone(),
+                }),
+            }
+        }
+        for new_table in &new.tables {
+            if !old.tables.iter().any(|t| t.name == new_table.name) {
+                changes.push(Change::TableAdded {
+                    table: new_table.name.clone(),
+                });
+            }
+        }
+
+        for old_enum in &old.enums {
+            match new.enums.iter().find(|e| e.name == old_enum.name) {
+                Some(new_enum) => diff_enums(old_enum, new_enum, &mut changes),
+                None => changes.push(Change::EnumRemoved {
+                    name: old_enum.name.clone(),
+                }),
+            }
+        }
+        for new_enum in &new.enums {
+            if !old.enums.iter().any(|e| e.name == new_enum.name) {
+                changes.push(Change::EnumAdded {
+                    name: new_enum.name.clone(),
+                });
+            }
+        }
+
+        Self { changes }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.changes.is_empty()
+    }
+
+    pub fn has_breaking_changes(&self) -> bool {
+        self.changes.iter().any(Change::is_breaking)
+    }
+
+    /// Render a human readable report
+    pub fn to_text(&self) -> String {
+        if self.changes.is_empty() {
+            return "No schema changes\n".to_string();
+        }
+
+        let breaking = self.changes.iter().filter(|c| c.is_breaking()).count();
+        let mut out = format!(
+            "{} schema change(s), {} breaking\n\n",
+            self.changes.len(),
+            breaking
+        );
+
+        for change in &self.changes {
+            let marker = if change.is_breaking() {
+                " [BREAKING]"
+            } else {
+                ""
+            };
+            out.push_str(&format!("  {} {}{}\n", change.symbol(), change, marker));
+        }
+
+        out
+    }
+
+    /// Render a machine readable report
+    pub fn to_json(&self) -> Result<String, serde_json::Error> {
+        let report = JsonDiff {
+            breaking: self.has_breaking_changes(),
+            changes: self
+                .changes
+                .iter()
+                .map(|change| JsonChange {
+                    change,
+                    breaking: change.is_breaking(),
+                })
+                .collect(),
+        };
+
+        serde_json::to_string_pretty(&report)
+    }
+}
+
+fn diff_tables(old: &Table, new: &Table, changes: &mut Vec<Change>) {
+    for old_col in &old.columns {
+        match new.columns.iter().find(|c| c.name == old_col.name) {
+            Some(new_col) => diff_columns(&old.name, old_col, new_col, changes),
+            None => changes.push(Change::ColumnRemoved {
+                table: old.name.clone(),
+                column: old_col.name.clone(),
+            }),
+        }
+    }
+    for new_col in &new.columns {
+        if !old.columns.iter().any(|c| c.name == new_col.name) {
+            changes.push(Change::ColumnAdded {
+                table: new.name.clone(),
+                column: new_col.name.clone(),
+                data_type: new_col.data_type.clone(),
+                is_nullable: new_col.is_nullable,
+                has_default: new_col.has_default,
+            });
+        }
+    }
+
+    if old.primary_key != new.primary_key {
+        changes.push(Change::PrimaryKeyChanged {
+            table: old.name.clone(),
+            from: old.primary_key.clone(),
+            to: new.primary_key.clone(),
+        });
+    }
+}
+
+fn diff_columns(table: &str, old: &Column, new: &Column, changes: &mut Vec<Change>) {
+    if old.data_type != new.data_type {
+        changes.push(Change::ColumnTypeChanged {
+            table: table.to_string(),
+            column: old.name.clone(),
+            from: old.data_type.clone(),
+            to: new.data_type.clone(),
+        });
+    }
+
+    if old.is_nullable != new.is_nullable {
+        changes.push(Change::ColumnNullabilityChanged {
+            table: table.to_string(),
+            column: old.name.clone(),
+            from_nullable: old.is_nullable,
+            to_nullable: new.is_nullable,
+        });
+    }
+}
+
+fn diff_enums(old: &EnumType, new: &EnumType, changes: &mut Vec<Change>) {
+    for value in &old.values {
+        if !new.values.contains(value) {
+            changes.push(Change::EnumValueRemoved {
+                name: old.name.clone(),
+                value: value.clone(),
+            });
+        }
+    }
+    for value in &new.values {
+        if !old.values.contains(value) {
+            changes.push(Change::EnumValueAdded {
+                name: new.name.clone(),
+                value: value.clone(),
+            });
+        }
+    }
+}
+
+/// Check whether a type change keeps the same generated type
+///
+/// Length changes and switches within the same family (e.g. `integer` to
+/// `bigint`, `varchar(50)` to `text`) map to the same target type.
+fn is_compatible_type(from: &DataType, to: &DataType) -> bool {
+    match (from, to) {
+        (DataType::Array(a), DataType::Array(b)) => is_compatible_type(a, b),
+        _ => type_family(from).is_some() && type_family(from) == type_family(to),
+    }
+}
+
+fn type_family(data_type: &DataType) -> Option<&'static str> {
+    match data_type {
+        DataType::SmallInt | DataType::Integer | DataType::BigInt => Some("int"),
+        DataType::Text | DataType::Varchar(_) | DataType::Char(_) => Some("str"),
+        DataType::Real | DataType::DoublePrecision => Some("float"),
+        DataType::Timestamp | DataType::TimestampTz => Some("datetime"),
+        DataType::Time | DataType::TimeTz => Some("time"),
+        DataType::Json | DataType::JsonBinary => Some("json"),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn column(name: &str, data_type: DataType, is_nullable: bool) -> Column {
+        Column {
+            name: name.to_string(),
+            data_type,
+            is_nullable,
+            has_default: false,
+            is_auto_generated: false,
+        }
+    }
+
+    fn schema(tables: Vec<Table>, enums: Vec<EnumType>) -> Schema {
+        Schema {
+            name: "public".to_string(),
+            tables,
+            enums,
+        }
+    }
+
+    fn users(columns: Vec<Column>) -> Table {
+        Table {
+            name: "users".to_string(),
+            columns,
+            primary_key: vec!["id".to_string()],
+            unique_constraints: vec![],
+            foreign_keys: vec![],
+        }
+    }
+
+    #[test]
+    fn test_identical_schemas() {
+        let s = schema(
+            vec![users(vec![column("id", DataType::Integer, false)])],
+            vec![],
+        );
+        let diff = SchemaDiff::between(&s, &s);
+        assert!(diff.is_empty());
+        assert!(!diff.has_breaking_changes());
+    }
+
+    #[test]
+    fn test_table_added_and_removed() {
+        let old = schema(vec![users(vec![])], vec![]);
+        let mut orders = users(vec![]);
+        orders.name = "orders".to_string();
+        let new = schema(vec![orders], vec![]);
+
+        let diff = SchemaDiff::between(&old, &new);
+        assert_eq!(
+            diff.changes,
+            vec![
+                Change::TableRemoved {
+                    table: "users".to_string()
+                },
+                Change::TableAdded {
+                    table: "orders".to_string()
+                },
+            ]
+        );
+        assert!(diff.changes[0].is_breaking());
+        assert!(!diff.changes[1].is_breaking());
+    }
+
+    #[test]
+    fn test_column_changes() {
+        let old = schema(
+            vec![users(vec![
+                column("id", DataType::Integer, false),
+                column("email", DataType::Varchar(Some(100)), true),
+                column("legacy", DataType::Text, true),
+            ])],
+            vec![],
+        );
+        let new = schema(
+            vec![users(vec![
+                column("id", DataType::Uuid, false),
+                column("email", DataType::Text, false),
+                column("nickname", DataType::Text, true),
+            ])],
+            vec![],
+        );
+
+        let diff = SchemaDiff::between(&old, &new);
+        let breaking: Vec<_> = diff.changes.iter().map(|c| c.is_breaking()).collect();
+
+        assert_eq!(diff.changes.len(), 5);
+        assert!(matches!(diff.changes[0], Change::ColumnTypeChanged { .. }));
+        assert!(matches!(diff.changes[1], Change::ColumnTypeChanged { .. }));
+        assert!(matches!(
+            diff.changes[2],
+            Change::ColumnNullabilityChanged { .. }
+        ));
+        assert!(matches!(diff.changes[3], Change::ColumnRemoved { .. }));
+        assert!(matches!(diff.changes[4], Change::ColumnAdded { .. }));
+        // integer -> uuid breaks, varchar -> text does not, nullable -> NOT NULL breaks
+        assert_eq!(breaking, vec![true, false, true, true, false]);
+    }
+
+    #[test]
+    fn test_required_column_added_is_breaking() {
+        let old = schema(vec![users(vec![])], vec![]);
+        let new = schema(
+            vec![users(vec![column("email", DataType::Text, false)])],
+            vec![],
+        );
+
+        let diff = SchemaDiff::between(&old, &new);
+        assert!(diff.has_breaking_changes());
+    }
+
+    #[test]
+    fn test_primary_key_changed() {
+        let old = schema(vec![users(vec![])], vec![]);
+        let mut table = users(vec![]);
+        table.primary_key = vec!["id".to_string(), "tenant_id".to_string()];
+        let new = schema(vec![table], vec![]);
+
+        let diff = SchemaDiff::between(&old, &new);
+        assert!(matches!(diff.changes[0], Change::PrimaryKeyChanged { .. }));
+        assert!(diff.has_breaking_changes());
+    }
+
+    #[test]
+    fn test_enum_values() {
+        let status = |values: &[&str]| EnumType {
+            name: "status".to_string(),
+            values: values.iter().map(|v| v.to_string()).collect(),
+        };
+        let old = schema(vec![], vec![status(&["active", "banned"])]);
+        let new = schema(vec![], vec![status(&["active", "suspended"])]);
+
+        let diff = SchemaDiff::between(&old, &new);
+        assert_eq!(
+            diff.changes,
+            vec![
+                Change::EnumValueRemoved {
+                    name: "status".to_string(),
+                    value: "banned".to_string()
+                },
+                Change::EnumValueAdded {
+                    name: "status".to_string(),
+                    value: "suspended".to_string()
+                },
+            ]
+        );
+        assert!(diff.changes[0].is_breaking());
+        assert!(!diff.changes[1].is_breaking());
+    }
+
+    #[test]
+    fn test_reports() {
+        let old = schema(
+            vec![users(vec![column("id", DataType::Integer, false)])],
+            vec![],
+        );
+        let new = schema(vec![users(vec![])], vec![]);
+        let diff = SchemaDiff::between(&old, &new);
+
+        let text = diff.to_text();
+        assert!(text.contains("1 schema change(s), 1 breaking"));
+        assert!(text.contains("- column users.id removed [BREAKING]"));
+
+        let json: serde_json::Value = serde_json::from_str(&diff.to_json().unwrap()).unwrap();
+        assert_eq!(json["breaking"], true);
+        assert_eq!(json["changes"][0]["kind"], "column_removed");
+        assert_eq!(json["changes"][0]["table"], "users");
+        assert_eq!(json["changes"][0]["breaking"], true);
+    }
+}
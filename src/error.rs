@@ -15,6 +15,12 @@ pub enum SqliftError {
     #[error("Failed to write output: {0}")]
     Output(#[from] std::io::Error),
 
+    #[error("Refusing to overwrite '{0}': it was not generated by sqlift (pass --force to overwrite it)")]
+    UnmanagedFile(String),
+
     #[error("Configuration error: {0}")]
     Config(String),
+
+    #[error("Invalid schema snapshot '{path}': {message}")]
+    Snapshot { path: String, message: String },
 }
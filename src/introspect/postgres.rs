@@ -1,9 +1,9 @@
 use postgres::Client;
-use tracing::{debug, error, info, trace};
+use tracing::{debug, error, info, trace, warn};
 
 use super::{Introspector, TableFilter};
 use crate::error::SqliftError;
-use crate::schema::{Column, DataType, EnumType, Schema, Table};
+use crate::schema::{Column, DataType, EnumType, ForeignKey, Schema, Table, UniqueConstraint};
 
 /// PostgreSQL introspector
 pub struct PostgresIntrospector<'a> {
@@ -46,10 +46,27 @@ impl Introspector for PostgresIntrospector<'_> {
             let primary_key = query_primary_key(self.client, schema_name, &table_name)?;
             trace!(table = ?table_name, primary_key = ?primary_key, "Found primary key");
 
+            let unique_constraints =
+                query_unique_constraints(self.client, schema_name, &table_name)?;
+            trace!(
+                table = ?table_name,
+                unique_constraints = ?unique_constraints.len(),
+                "Found unique constraints"
+            );
+
+            let foreign_keys = query_foreign_keys(self.client, schema_name, &table_name)?;
+            trace!(
+                table = ?table_name,
+                foreign_keys = ?foreign_keys.len(),
+                "Found foreign keys"
+            );
+
             tables.push(Table {
                 name: table_name,
                 columns,
                 primary_key,
+                unique_constraints,
+                foreign_keys,
             });
         }
 
@@ -210,6 +227,153 @@ fn query_primary_key(
     Ok(pk_columns)
 }
 
+/// Query unique constraints (excluding the primary key) for a table
+fn query_unique_constraints(
+    client: &mut Client,
+    schema_name: &str,
+    table_name: &str,
+) -> Result<Vec<UniqueConstraint>, SqliftError> {
+    trace!(schema = ?schema_name, table = ?table_name, "Querying unique constraints");
+
+    let sql = r#"
+        SELECT
+            con.conname::text AS constraint_name,
+            array_agg(a.attname::text ORDER BY array_position(con.conkey, a.attnum)) AS columns
+        FROM pg_constraint con
+        JOIN pg_class c ON c.oid = con.conrelid
+        JOIN pg_namespace n ON n.oid = c.relnamespace
+        JOIN pg_attribute a ON a.attrelid = c.oid AND a.attnum = ANY(con.conkey)
+        WHERE con.contype = 'u'
+            AND c.relname = $1
+            AND n.nspname = $2
+        GROUP BY con.conname
+        ORDER BY con.conname
+    "#;
+
+    let rows = client
+        .query(sql, &[&table_name, &schema_name])
+        .map_err(|e| {
+            error!(
+                schema = ?schema_name,
+                table = ?table_name,
+                error = ?e,
+                "Failed to query unique constraints"
+            );
+            SqliftError::Introspection {
+                schema: schema_name.to_string(),
+                message: format!(
+                    "Failed to query unique constraints for table '{}': {}",
+                    table_name, e
+                ),
+            }
+        })?;
+
+    let constraints = rows
+        .iter()
+        .map(|row| UniqueConstraint {
+            name: row.get("constraint_name"),
+            columns: row.get("columns"),
+        })
+        .collect();
+    Ok(constraints)
+}
+
+/// Query foreign keys for a table
+fn query_foreign_keys(
+    client: &mut Client,
+    schema_name: &str,
+    table_name: &str,
+) -> Result<Vec<ForeignKey>, SqliftError> {
+    trace!(schema = ?schema_name, table = ?table_name, "Querying foreign keys");
+
+    let sql = r#"
+        SELECT
+            con.conname::text AS constraint_name,
+            ARRAY(
+                SELECT a.attname::text
+                FROM unnest(con.conkey) WITH ORDINALITY AS k(attnum, ord)
+                JOIN pg_attribute a ON a.attrelid = con.conrelid AND a.attnum = k.attnum
+                ORDER BY k.ord
+            ) AS columns,
+            rc.relname::text AS referenced_table,
+            rn.nspname::text AS referenced_schema,
+            ARRAY(
+                SELECT a.attname::text
+                FROM unnest(con.confkey) WITH ORDINALITY AS k(attnum, ord)
+                JOIN pg_attribute a ON a.attrelid = con.confrelid AND a.attnum = k.attnum
+                ORDER BY k.ord
+            ) AS referenced_columns
+        FROM pg_constraint con
+        JOIN pg_class c ON c.oid = con.conrelid
+        JOIN pg_namespace n ON n.oid = c.relnamespace
+        JOIN pg_class rc ON rc.oid = con.confrelid
+        JOIN pg_namespace rn ON rn.oid = rc.relnamespace
+        WHERE con.contype = 'f'
+            AND c.relname = $1
+            AND n.nspname = $2
+        ORDER BY con.conname
+    "#;
+
+    let rows = client
+        .query(sql, &[&table_name, &schema_name])
+        .map_err(|e| {
+            error!(
+                schema = ?schema_name,
+                table = ?table_name,
+                error = ?e,
+                "Failed to query foreign keys"
+            );
+            SqliftError::Introspection {
+                schema: schema_name.to_string(),
+                message: format!(
+                    "Failed to query foreign keys for table '{}': {}",
+                    table_name, e
+                ),
+            }
+        })?;
+
+    let foreign_keys = rows
+        .iter()
+        .map(|row| {
+            let foreign_key = ForeignKey {
+                name: row.get("constraint_name"),
+                columns: row.get("columns"),
+                referenced_table: row.get("referenced_table"),
+                referenced_columns: row.get("referenced_columns"),
+            };
+            (row.get("referenced_schema"), foreign_key)
+        })
+        .collect();
+    Ok(same_schema_foreign_keys(
+        schema_name,
+        table_name,
+        foreign_keys,
+    ))
+}
+
+/// Drop foreign keys to tables in other schemas, which the snapshot can't name
+fn same_schema_foreign_keys(
+    schema_name: &str,
+    table_name: &str,
+    foreign_keys: Vec<(String, ForeignKey)>,
+) -> Vec<ForeignKey> {
+    foreign_keys
+        .into_iter()
+        .filter_map(|(referenced_schema, foreign_key)| {
+            if referenced_schema == schema_name {
+                return Some(foreign_key);
+            }
+            warn!(
+                table = ?table_name,
+                foreign_key = ?foreign_key.name,
+                referenced_schema = ?referenced_schema,
+                "Skipping foreign key to another schema"
+            );
+            None
+        })
+        .collect()
+}
+
 /// Query all enum types in a schema
 fn query_enums(client: &mut Client, schema_name: &str) -> Result<Vec<EnumType>, SqliftError> {
     trace!(schema = ?schema_name, "Querying enum types");
@@ -278,8 +442,7 @@ fn parse_data_type(type_str: &str) -> DataType {
     let trimmed = lower.trim();
 
     // Handle arrays first (e.g., "integer[]", "text[]", "character varying(255)[]")
-    if trimmed.ends_with("[]") {
-        let inner_type = &trimmed[..trimmed.len() - 2];
+    if let Some(inner_type) = trimmed.strip_suffix("[]") {
         let inner = parse_data_type(inner_type);
         return DataType::Array(Box::new(inner));
     }
@@ -432,6 +595,36 @@ mod tests {
         assert!(!is_auto_generated_column(&None));
     }
 
+    #[test]
+    fn test_same_schema_foreign_keys() {
+        let foreign_key = |name: &str, referenced_table: &str| ForeignKey {
+            name: name.to_string(),
+            columns: vec!["user_id".to_string()],
+            referenced_table: referenced_table.to_string(),
+            referenced_columns: vec!["id".to_string()],
+        };
+        let keys = vec![
+            (
+                "public".to_string(),
+                foreign_key("orders_user_id_fkey", "users"),
+            ),
+            (
+                "auth".to_string(),
+                foreign_key("orders_auth_user_id_fkey", "users"),
+            ),
+        ];
+
+        // public.users and auth.users share a name, so only the local key is kept
+        assert_eq!(
+            same_schema_foreign_keys("public", "orders", keys.clone()),
+            vec![foreign_key("orders_user_id_fkey", "users")]
+        );
+        assert_eq!(
+            same_schema_foreign_keys("auth", "orders", keys),
+            vec![foreign_key("orders_auth_user_id_fkey", "users")]
+        );
+    }
+
     #[test]
     fn test_extract_length() {
         assert_eq!(extract_length("varchar(255)"), Some(255));
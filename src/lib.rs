@@ -7,16 +7,24 @@
 
 pub mod codegen;
 pub mod config;
+pub mod diff;
 pub mod error;
 pub mod introspect;
 pub mod schema;
+pub mod snapshot;
 
 pub mod prelude {
-    pub use crate::codegen::{CodeGenConfig, CodeGenerator, FunctionStyle, OutputMode};
+    pub use crate::codegen::{
+        CodeGenConfig, CodeGenerator, FunctionStyle, GeneratedFiles, OutputMode,
+    };
     pub use crate::config::DbConfig;
+    pub use crate::diff::{Change, SchemaDiff};
     pub use crate::error::SqliftError;
     pub use crate::introspect::{Introspector, TableFilter};
-    pub use crate::schema::{to_pascal_case, Column, DataType, EnumType, Schema, Table};
+    pub use crate::schema::{
+        to_pascal_case, Column, DataType, EnumType, ForeignKey, Schema, Table, UniqueConstraint,
+    };
+    pub use crate::snapshot::{Snapshot, SnapshotFormat};
 }
 
 #[cfg(feature = "postgres")]
@@ -1,13 +1,18 @@
 use anyhow::{bail, Context, Result};
-use clap::{Parser, ValueEnum};
-use std::path::PathBuf;
+use clap::{Args, Parser, Subcommand, ValueEnum};
+use std::path::{Path, PathBuf};
 use tracing::{debug, error, info, warn, Level};
 use tracing_subscriber::FmtSubscriber;
 
-use sqlift::codegen::{CodeGenConfig, CodeGenerator, FunctionStyle, OutputMode, PythonGenerator};
+use sqlift::codegen::{
+    CodeGenConfig, CodeGenerator, FunctionStyle, GeneratedFiles, OutputMode, PythonDriver,
+    PythonGenerator, PythonModels, SqlAlchemyGenerator, DEFAULT_SOFT_DELETE_COLUMN,
+};
 use sqlift::config::DbConfig;
-use sqlift::introspect::{Introspector, TableFilter};
+use sqlift::diff::SchemaDiff;
+use sqlift::introspect::TableFilter;
 use sqlift::schema::Schema;
+use sqlift::snapshot::Snapshot;
 
 #[derive(Debug, Clone, ValueEnum)]
 enum Database {
@@ -16,7 +21,10 @@ enum Database {
 
 #[derive(Debug, Clone, ValueEnum)]
 enum Language {
+    /// psycopg data access functions
     Python,
+    /// SQLAlchemy Core tables with typed query helpers
+    Sqlalchemy,
 }
 
 #[derive(Debug, Clone, Copy, ValueEnum, Default)]
@@ -55,10 +63,72 @@ impl From<CliFunctionStyle> for FunctionStyle {
     }
 }
 
+#[derive(Debug, Clone, Copy, ValueEnum, Default)]
+enum CliPythonModels {
+    /// Standard library dataclasses
+    #[default]
+    Dataclass,
+    /// Pydantic v2 models
+    Pydantic,
+    /// attrs classes
+    Attrs,
+    /// msgspec structs
+    Msgspec,
+}
+
+impl From<CliPythonModels> for PythonModels {
+    fn from(models: CliPythonModels) -> Self {
+        match models {
+            CliPythonModels::Dataclass => PythonModels::Dataclass,
+            CliPythonModels::Pydantic => PythonModels::Pydantic,
+            CliPythonModels::Attrs => PythonModels::Attrs,
+            CliPythonModels::Msgspec => PythonModels::Msgspec,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, ValueEnum, Default)]
+enum CliPythonDriver {
+    /// psycopg 3
+    #[default]
+    Psycopg,
+    /// psycopg2
+    Psycopg2,
+}
+
+impl From<CliPythonDriver> for PythonDriver {
+    fn from(driver: CliPythonDriver) -> Self {
+        match driver {
+            CliPythonDriver::Psycopg => PythonDriver::Psycopg,
+            CliPythonDriver::Psycopg2 => PythonDriver::Psycopg2,
+        }
+    }
+}
+
 #[derive(Parser, Debug)]
 #[command(name = "sqlift")]
 #[command(version, about, long_about = None)]
 struct Cli {
+    #[command(subcommand)]
+    command: Command,
+
+    /// Verbose output (-v for debug, -vv for trace)
+    #[arg(short, long, action = clap::ArgAction::Count, global = true)]
+    verbose: u8,
+}
+
+#[derive(Subcommand, Debug)]
+enum Command {
+    /// Generate data access code from a database or schema snapshot
+    Generate(GenerateArgs),
+    /// Introspect a database and save the schema as a snapshot
+    Introspect(IntrospectArgs),
+    /// Compare two schema snapshots, or a snapshot and the live database
+    Diff(DiffArgs),
+}
+
+#[derive(Args, Debug)]
+struct GenerateArgs {
     /// Target database type
     database: Database,
 
@@ -77,6 +147,111 @@ struct Cli {
     #[arg(long, value_enum, default_value_t = CliFunctionStyle::Standalone)]
     style: CliFunctionStyle,
 
+    /// Class library for generated Python records and parameters
+    #[arg(long, value_enum, default_value_t = CliPythonModels::Dataclass)]
+    python_models: CliPythonModels,
+
+    /// Generate immutable records (frozen, slotted dataclasses)
+    #[arg(long)]
+    frozen_records: bool,
+
+    /// Database driver used by the generated Python code
+    #[arg(long, value_enum, default_value_t = CliPythonDriver::Psycopg)]
+    python_driver: CliPythonDriver,
+
+    /// Generate pytest smoke tests in a tests/ package next to the output
+    #[arg(long)]
+    tests: bool,
+
+    /// Generate from a schema snapshot instead of connecting to the database
+    #[arg(long)]
+    from_snapshot: Option<PathBuf>,
+
+    /// Check that the generated files are up to date instead of writing them
+    #[arg(long, conflicts_with = "dry_run")]
+    check: bool,
+
+    /// Print the files that would be generated without writing them
+    #[arg(long)]
+    dry_run: bool,
+
+    /// Overwrite files that were not generated by sqlift and prune modified stale files
+    ///
+    /// Needed once when generating into existing output without a manifest,
+    /// such as a package generated by an older version. Files that already
+    /// match the generated code are adopted without it.
+    #[arg(long)]
+    force: bool,
+
+    /// Optimistic locking column for tables that have it (an integer column or xmin)
+    #[arg(long)]
+    version_column: Option<String>,
+
+    /// Comma-separated per-table version columns as table=column
+    #[arg(long, value_delimiter = ',', value_parser = parse_table_column)]
+    table_version_column: Vec<(String, String)>,
+
+    /// Soft delete tables that have this timestamp column (default: deleted_at)
+    #[arg(long, num_args = 0..=1, default_missing_value = DEFAULT_SOFT_DELETE_COLUMN)]
+    soft_delete_column: Option<String>,
+
+    /// Comma-separated per-table soft delete columns as table=column
+    #[arg(long, value_delimiter = ',', value_parser = parse_table_column)]
+    table_soft_delete_column: Vec<(String, String)>,
+
+    #[command(flatten)]
+    source: SourceArgs,
+}
+
+#[derive(Args, Debug)]
+struct IntrospectArgs {
+    /// Database type to introspect
+    database: Database,
+
+    /// Snapshot file path (.json, .yaml or .yml)
+    #[arg(short, long, default_value = "./schema.json")]
+    output: PathBuf,
+
+    #[command(flatten)]
+    source: SourceArgs,
+}
+
+#[derive(Args, Debug)]
+struct DiffArgs {
+    /// Old schema snapshot
+    old: PathBuf,
+
+    /// New schema snapshot (default: introspect the live database)
+    new: Option<PathBuf>,
+
+    /// Database type to introspect when no new snapshot is given
+    #[arg(long, value_enum, default_value_t = Database::Postgres)]
+    database: Database,
+
+    /// Report format
+    #[arg(long, value_enum, default_value_t = DiffFormat::Text)]
+    format: DiffFormat,
+
+    /// Exit with an error if any change breaks the generated API
+    #[arg(long)]
+    fail_on_breaking: bool,
+
+    #[command(flatten)]
+    source: SourceArgs,
+}
+
+#[derive(Debug, Clone, Copy, ValueEnum, Default)]
+enum DiffFormat {
+    /// Human readable report
+    #[default]
+    Text,
+    /// Machine readable JSON report
+    Json,
+}
+
+/// Options shared by every command that reads a schema
+#[derive(Args, Debug)]
+struct SourceArgs {
     /// Database schema to introspect
     #[arg(long, default_value = "public")]
     schema: String,
@@ -92,10 +267,31 @@ struct Cli {
     /// Comma-separated list of tables to exclude
     #[arg(long, value_delimiter = ',')]
     exclude: Option<Vec<String>>,
+}
 
-    /// Verbose output (-v for debug, -vv for trace)
-    #[arg(short, long, action = clap::ArgAction::Count)]
-    verbose: u8,
+impl SourceArgs {
+    fn filter(&self) -> TableFilter {
+        let filter = TableFilter {
+            include: self.tables.clone(),
+            exclude: self.exclude.clone(),
+        };
+
+        if filter.include.is_some() || filter.exclude.is_some() {
+            debug!(filter = ?filter, "Table filter configured");
+        }
+
+        filter
+    }
+}
+
+/// Parse a `table=column` argument
+fn parse_table_column(value: &str) -> Result<(String, String), String> {
+    match value.split_once('=') {
+        Some((table, column)) if !table.is_empty() && !column.is_empty() => {
+            Ok((table.to_string(), column.to_string()))
+        }
+        _ => Err(format!("expected table=column, got {}", value)),
+    }
 }
 
 fn main() {
@@ -111,33 +307,35 @@ fn run() -> Result<()> {
     init_tracing(cli.verbose);
 
     info!("sqlift v{}", env!("CARGO_PKG_VERSION"));
+
+    match cli.command {
+        Command::Generate(args) => run_generate(args),
+        Command::Introspect(args) => run_introspect(args),
+        Command::Diff(args) => run_diff(args),
+    }
+}
+
+fn run_generate(args: GenerateArgs) -> Result<()> {
     info!(
-        database = ?cli.database,
-        language = ?cli.language,
-        output = ?cli.output,
-        mode = ?cli.mode,
-        style = ?cli.style,
-        schema = ?cli.schema,
+        database = ?args.database,
+        language = ?args.language,
+        output = ?args.output,
+        mode = ?args.mode,
+        style = ?args.style,
+        schema = ?args.source.schema,
         "Starting code generation"
     );
 
-    // Load configuration
-    let config = DbConfig::load(&cli.env_file).context("Failed to load database configuration")?;
-    debug!(connection = ?config.redacted_connection_string(), "Loaded configuration");
+    let filter = args.source.filter();
 
-    // Build table filter
-    let filter = TableFilter {
-        include: cli.tables,
-        exclude: cli.exclude,
+    let schema = match &args.from_snapshot {
+        Some(path) => load_snapshot(path, &filter)?,
+        None => {
+            let config = load_db_config(&args.source.env_file)?;
+            introspect_database(&args.database, &config, &args.source.schema, &filter)?
+        }
     };
 
-    if filter.include.is_some() || filter.exclude.is_some() {
-        debug!(filter = ?filter, "Table filter configured");
-    }
-
-    // Introspect database
-    let schema = introspect_database(&cli.database, &config, &cli.schema, &filter)?;
-
     if schema.tables.is_empty() {
         warn!("No tables found after filtering");
         return Ok(());
@@ -159,19 +357,122 @@ fn run() -> Result<()> {
         );
     }
 
-    let codegen_config = CodeGenConfig::new(cli.output)
-        .with_output_mode(cli.mode.into())
-        .with_function_style(cli.style.into());
+    let mut codegen_config = CodeGenConfig::new(args.output)
+        .with_output_mode(args.mode.into())
+        .with_function_style(args.style.into())
+        .with_python_models(args.python_models.into())
+        .with_frozen_records(args.frozen_records)
+        .with_python_driver(args.python_driver.into())
+        .with_tests(args.tests)
+        .with_force(args.force);
+    if let Some(column) = args.version_column {
+        codegen_config = codegen_config.with_version_column(column);
+    }
+    for (table, column) in args.table_version_column {
+        codegen_config = codegen_config.with_table_version_column(table, column);
+    }
+    if let Some(column) = args.soft_delete_column {
+        codegen_config = codegen_config.with_soft_delete_column(column);
+    }
+    for (table, column) in args.table_soft_delete_column {
+        codegen_config = codegen_config.with_table_soft_delete_column(table, column);
+    }
     debug!(codegen_config = ?codegen_config, "Code generation config");
 
+    if args.check {
+        return check_code(&args.language, &schema, &codegen_config);
+    }
+    if args.dry_run {
+        return dry_run_code(&args.language, &schema, &codegen_config);
+    }
+
     // Generate code based on target language
-    generate_code(&cli.language, &schema, &codegen_config)?;
+    generate_code(&args.language, &schema, &codegen_config)?;
 
     info!("Code generation complete");
 
     Ok(())
 }
 
+fn run_introspect(args: IntrospectArgs) -> Result<()> {
+    info!(
+        database = ?args.database,
+        output = ?args.output,
+        schema = ?args.source.schema,
+        "Starting introspection"
+    );
+
+    let filter = args.source.filter();
+    let config = load_db_config(&args.source.env_file)?;
+    let schema = introspect_database(&args.database, &config, &args.source.schema, &filter)?;
+
+    info!(
+        tables = ?schema.tables.len(),
+        enums = ?schema.enums.len(),
+        "Schema introspected"
+    );
+
+    Snapshot::new(schema)
+        .save(&args.output)
+        .with_context(|| format!("Failed to write snapshot to {}", args.output.display()))?;
+
+    Ok(())
+}
+
+fn run_diff(args: DiffArgs) -> Result<()> {
+    let filter = args.source.filter();
+    let old = load_snapshot(&args.old, &filter)?;
+
+    let new = match &args.new {
+        Some(path) => load_snapshot(path, &filter)?,
+        None => {
+            let config = load_db_config(&args.source.env_file)?;
+            introspect_database(&args.database, &config, &args.source.schema, &filter)?
+        }
+    };
+
+    let diff = SchemaDiff::between(&old, &new);
+    info!(
+        changes = ?diff.changes.len(),
+        breaking = ?diff.has_breaking_changes(),
+        "Schema diff complete"
+    );
+
+    match args.format {
+        DiffFormat::Text => print!("{}", diff.to_text()),
+        DiffFormat::Json => println!(
+            "{}",
+            diff.to_json().context("Failed to serialize schema diff")?
+        ),
+    }
+
+    if args.fail_on_breaking && diff.has_breaking_changes() {
+        bail!("Schema diff contains breaking changes");
+    }
+
+    Ok(())
+}
+
+fn load_db_config(env_file: &Path) -> Result<DbConfig> {
+    let config = DbConfig::load(env_file).context("Failed to load database configuration")?;
+    debug!(connection = ?config.redacted_connection_string(), "Loaded configuration");
+    Ok(config)
+}
+
+/// Load a snapshot and apply the table filter to it
+fn load_snapshot(path: &Path, filter: &TableFilter) -> Result<Schema> {
+    info!(path = ?path, "Loading schema from snapshot");
+
+    let mut schema = Snapshot::load(path)
+        .with_context(|| format!("Failed to load snapshot {}", path.display()))?
+        .schema;
+    schema
+        .tables
+        .retain(|table| filter.should_include(&table.name));
+
+    Ok(schema)
+}
+
 fn init_tracing(verbose: u8) {
     let level = match verbose {
         0 => Level::INFO,
@@ -182,6 +483,7 @@ fn init_tracing(verbose: u8) {
     let subscriber = FmtSubscriber::builder()
         .with_max_level(level)
         .with_target(false)
+        .with_writer(std::io::stderr)
         .finish();
 
     tracing::subscriber::set_global_default(subscriber).expect("Failed to set tracing subscriber");
@@ -198,18 +500,71 @@ fn introspect_database(
     }
 }
 
-fn generate_code(language: &Language, schema: &Schema, config: &CodeGenConfig) -> Result<()> {
+/// Render code for the target language without writing anything
+fn render_code(
+    language: &Language,
+    schema: &Schema,
+    config: &CodeGenConfig,
+) -> Result<GeneratedFiles> {
     match language {
-        Language::Python => {
-            let generator = PythonGenerator::new();
-            generator
-                .generate(schema, config)
-                .context("Python code generation failed")?;
-        }
+        Language::Python => PythonGenerator::new()
+            .render(schema, config)
+            .context("Python code generation failed"),
+        Language::Sqlalchemy => SqlAlchemyGenerator::new()
+            .render(schema, config)
+            .context("SQLAlchemy code generation failed"),
+    }
+}
+
+fn generate_code(language: &Language, schema: &Schema, config: &CodeGenConfig) -> Result<()> {
+    let files = render_code(language, schema, config)?;
+    files
+        .write(config.force)
+        .context("Failed to write generated code")?;
+    Ok(())
+}
+
+/// List the files that would be written without touching the filesystem
+fn dry_run_code(language: &Language, schema: &Schema, config: &CodeGenConfig) -> Result<()> {
+    let files = render_code(language, schema, config)?;
+
+    for (path, contents) in files.iter() {
+        println!(
+            "{} ({} bytes)",
+            files.root().join(path).display(),
+            contents.len()
+        );
     }
+
+    info!(files = ?files.len(), "Dry run complete, nothing written");
     Ok(())
 }
 
+/// Render in memory and report every file that differs from what is on disk
+fn check_code(language: &Language, schema: &Schema, config: &CodeGenConfig) -> Result<()> {
+    let files = render_code(language, schema, config)?;
+
+    let stale = files
+        .check()
+        .context("Failed to compare generated code with existing files")?;
+
+    if stale.is_empty() {
+        info!(files = ?files.len(), "Generated code is up to date");
+        return Ok(());
+    }
+
+    for file in &stale {
+        warn!(path = ?file.path, "Generated file is out of date");
+        print!("{}", file.diff);
+    }
+
+    bail!(
+        "{} of {} generated file(s) are out of date",
+        stale.len(),
+        files.len()
+    )
+}
+
 #[cfg(feature = "postgres")]
 fn introspect_postgres(
     config: &DbConfig,
@@ -217,6 +572,7 @@ fn introspect_postgres(
     filter: &TableFilter,
 ) -> Result<Schema> {
     use postgres::NoTls;
+    use sqlift::introspect::Introspector;
     use sqlift::PostgresIntrospector;
 
     info!(connection = ?config.redacted_connection_string(), "Connecting to PostgreSQL");
@@ -3,8 +3,12 @@
 //! These types represent database schema information and form the contract
 //! between introspection (produces) and code generation (consumes).
 
+use std::fmt;
+
+use serde::{Deserialize, Serialize};
+
 /// A complete database schema
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Schema {
     pub name: String,
     pub tables: Vec<Table>,
@@ -12,12 +16,38 @@ pub struct Schema {
 }
 
 /// Database table
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Table {
     pub name: String,
     pub columns: Vec<Column>,
     /// Column names that form the primary key (in order)
     pub primary_key: Vec<String>,
+    /// Unique constraints other than the primary key
+    #[serde(default)]
+    pub unique_constraints: Vec<UniqueConstraint>,
+    /// Foreign keys referencing other tables
+    #[serde(default)]
+    pub foreign_keys: Vec<ForeignKey>,
+}
+
+/// A UNIQUE constraint on one or more columns
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct UniqueConstraint {
+    pub name: String,
+    /// Constrained column names (in order)
+    pub columns: Vec<String>,
+}
+
+/// A FOREIGN KEY constraint on one or more columns
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ForeignKey {
+    pub name: String,
+    /// Referencing column names (in order)
+    pub columns: Vec<String>,
+    /// Referenced table name
+    pub referenced_table: String,
+    /// Referenced column names, matching `columns` by position
+    pub referenced_columns: Vec<String>,
 }
 
 impl Table {
@@ -103,6 +133,14 @@ impl Table {
         cols
     }
 
+    /// Get columns that can be written by an upsert (excludes auto-generated columns)
+    pub fn upsert_columns(&self) -> Vec<&Column> {
+        self.columns
+            .iter()
+            .filter(|col| !col.is_auto_generated)
+            .collect()
+    }
+
     /// Get non-primary-key columns (for update SET clause)
     pub fn non_pk_columns(&self) -> Vec<&Column> {
         self.columns
@@ -113,7 +151,7 @@ impl Table {
 }
 
 /// A table column
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Column {
     pub name: String,
     pub data_type: DataType,
@@ -124,7 +162,8 @@ pub struct Column {
     pub is_auto_generated: bool,
 }
 
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", content = "value", rename_all = "snake_case")]
 pub enum DataType {
     SmallInt,
     Integer,
@@ -150,8 +189,38 @@ pub enum DataType {
     Enum(String),
 }
 
+impl fmt::Display for DataType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DataType::SmallInt => write!(f, "smallint"),
+            DataType::Integer => write!(f, "integer"),
+            DataType::BigInt => write!(f, "bigint"),
+            DataType::Boolean => write!(f, "boolean"),
+            DataType::Text => write!(f, "text"),
+            DataType::Varchar(Some(len)) => write!(f, "varchar({})", len),
+            DataType::Varchar(None) => write!(f, "varchar"),
+            DataType::Char(Some(len)) => write!(f, "char({})", len),
+            DataType::Char(None) => write!(f, "char"),
+            DataType::Real => write!(f, "real"),
+            DataType::DoublePrecision => write!(f, "double precision"),
+            DataType::Numeric => write!(f, "numeric"),
+            DataType::Timestamp => write!(f, "timestamp"),
+            DataType::TimestampTz => write!(f, "timestamptz"),
+            DataType::Date => write!(f, "date"),
+            DataType::Time => write!(f, "time"),
+            DataType::TimeTz => write!(f, "timetz"),
+            DataType::Uuid => write!(f, "uuid"),
+            DataType::Json => write!(f, "json"),
+            DataType::JsonBinary => write!(f, "jsonb"),
+            DataType::Binary => write!(f, "bytea"),
+            DataType::Array(inner) => write!(f, "{}[]", inner),
+            DataType::Enum(name) => write!(f, "{}", name),
+        }
+    }
+}
+
 /// A custom enum type defined in the database
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct EnumType {
     pub name: String,
     pub values: Vec<String>,
@@ -185,6 +254,8 @@ mod tests {
             name: "users".to_string(),
             columns: vec![],
             primary_key: vec![],
+            unique_constraints: vec![],
+            foreign_keys: vec![],
         };
         assert_eq!(table.class_name(), "Users");
     }
@@ -195,6 +266,8 @@ mod tests {
             name: "user_accounts".to_string(),
             columns: vec![],
             primary_key: vec![],
+            unique_constraints: vec![],
+            foreign_keys: vec![],
         };
         assert_eq!(table.class_name(), "UserAccounts");
     }
@@ -205,6 +278,8 @@ mod tests {
             name: "order_line_items".to_string(),
             columns: vec![],
             primary_key: vec![],
+            unique_constraints: vec![],
+            foreign_keys: vec![],
         };
         assert_eq!(table.class_name(), "OrderLineItems");
     }
@@ -215,6 +290,8 @@ mod tests {
             name: "users".to_string(),
             columns: vec![],
             primary_key: vec![],
+            unique_constraints: vec![],
+            foreign_keys: vec![],
         };
         assert_eq!(table.singular_class_name(), "User");
     }
@@ -225,6 +302,8 @@ mod tests {
             name: "categories".to_string(),
             columns: vec![],
             primary_key: vec![],
+            unique_constraints: vec![],
+            foreign_keys: vec![],
         };
         assert_eq!(table.singular_class_name(), "Category");
     }
@@ -235,6 +314,8 @@ mod tests {
             name: "staff".to_string(),
             columns: vec![],
             primary_key: vec![],
+            unique_constraints: vec![],
+            foreign_keys: vec![],
         };
         assert_eq!(table.singular_class_name(), "Staff");
     }
@@ -251,6 +332,8 @@ mod tests {
                 is_auto_generated: true,
             }],
             primary_key: vec!["id".to_string()],
+            unique_constraints: vec![],
+            foreign_keys: vec![],
         };
         assert!(table.has_auto_generated_pk());
     }
@@ -267,6 +350,8 @@ mod tests {
                 is_auto_generated: false,
             }],
             primary_key: vec!["id".to_string()],
+            unique_constraints: vec![],
+            foreign_keys: vec![],
         };
         assert!(!table.has_auto_generated_pk());
     }
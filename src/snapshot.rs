@@ -0,0 +1,195 @@
+//! Schema snapshots
+//!
+//! Serializes an introspected schema to a versioned JSON or YAML file so it can
+//! be committed, reviewed, and used for code generation without a database.
+
+use std::fs;
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+use tracing::{debug, error, info};
+
+use crate::prelude::{Schema, SqliftError};
+
+/// Current snapshot format version
+///
+/// Bump this when the serialized schema layout changes incompatibly.
+pub const SNAPSHOT_VERSION: u32 = 1;
+
+/// Serialization format for snapshot files
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum SnapshotFormat {
+    #[default]
+    Json,
+    Yaml,
+}
+
+impl SnapshotFormat {
+    /// Pick the format from a file extension (`.yaml`/`.yml` or JSON otherwise)
+    pub fn from_path(path: &Path) -> Self {
+        match path.extension().and_then(|ext| ext.to_str()) {
+            Some("yaml") | Some("yml") => SnapshotFormat::Yaml,
+            _ => SnapshotFormat::Json,
+        }
+    }
+}
+
+/// A versioned, serializable schema
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Snapshot {
+    pub version: u32,
+    pub schema: Schema,
+}
+
+impl Snapshot {
+    pub fn new(schema: Schema) -> Self {
+        Self {
+            version: SNAPSHOT_VERSION,
+            schema,
+        }
+    }
+
+    /// Serialize the snapshot in the given format
+    pub fn to_string(&self, format: SnapshotFormat) -> Result<String, String> {
+        match format {
+            SnapshotFormat::Json => serde_json::to_string_pretty(self)
+                .map(|s| s + "\n")
+                .map_err(|e| e.to_string()),
+            SnapshotFormat::Yaml => serde_yaml::to_string(self).map_err(|e| e.to_string()),
+        }
+    }
+
+    /// Parse a snapshot in the given format and check its version
+    pub fn from_str(contents: &str, format: SnapshotFormat) -> Result<Self, String> {
+        let snapshot: Snapshot = match format {
+            SnapshotFormat::Json => serde_json::from_str(contents).map_err(|e| e.to_string())?,
+            SnapshotFormat::Yaml => serde_yaml::from_str(contents).map_err(|e| e.to_string())?,
+        };
+
+        if snapshot.version != SNAPSHOT_VERSION {
+            return Err(format!(
+                "unsupported snapshot version {} (expected {})",
+                snapshot.version, SNAPSHOT_VERSION
+            ));
+        }
+
+        Ok(snapshot)
+    }
+
+    /// Write the snapshot to a file, choosing the format from its extension
+    pub fn save(&self, path: &Path) -> Result<(), SqliftError> {
+        let format = SnapshotFormat::from_path(path);
+        let contents = self.to_string(format).map_err(|message| {
+            error!(path = ?path, error = ?message, "Failed to serialize snapshot");
+            SqliftError::Snapshot {
+                path: path.display().to_string(),
+                message,
+            }
+        })?;
+
+        if let Some(parent) = path.parent() {
+            if !parent.as_os_str().is_empty() {
+                fs::create_dir_all(parent)?;
+            }
+        }
+
+        fs::write(path, contents)?;
+        info!(path = ?path, format = ?format, "Wrote schema snapshot");
+
+        Ok(())
+    }
+
+    /// Read a snapshot from a file, choosing the format from its extension
+    pub fn load(path: &Path) -> Result<Self, SqliftError> {
+        let format = SnapshotFormat::from_path(path);
+        debug!(path = ?path, format = ?format, "Loading schema snapshot");
+
+        let contents = fs::read_to_string(path)?;
+        Self::from_str(&contents, format).map_err(|message| {
+            error!(path = ?path, error = ?message, "Failed to parse snapshot");
+            SqliftError::Snapshot {
+                path: path.display().to_string(),
+                message,
+            }
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::schema::{Column, DataType, EnumType, Table};
+
+    fn sample_schema() -> Schema {
+        Schema {
+            name: "public".to_string(),
+            tables: vec![Table {
+                name: "users".to_string(),
+                columns: vec![
+                    Column {
+                        name: "id".to_string(),
+                        data_type: DataType::Integer,
+                        is_nullable: false,
+                        has_default: true,
+                        is_auto_generated: true,
+                    },
+                    Column {
+                        name: "tags".to_string(),
+                        data_type: DataType::Array(Box::new(DataType::Varchar(Some(32)))),
+                        is_nullable: true,
+                        has_default: false,
+                        is_auto_generated: false,
+                    },
+                ],
+                primary_key: vec!["id".to_string()],
+                unique_constraints: vec![],
+                foreign_keys: vec![],
+            }],
+            enums: vec![EnumType {
+                name: "user_role".to_string(),
+                values: vec!["admin".to_string(), "member".to_string()],
+            }],
+        }
+    }
+
+    #[test]
+    fn test_format_from_path() {
+        assert_eq!(
+            SnapshotFormat::from_path(Path::new("schema.yaml")),
+            SnapshotFormat::Yaml
+        );
+        assert_eq!(
+            SnapshotFormat::from_path(Path::new("schema.yml")),
+            SnapshotFormat::Yaml
+        );
+        assert_eq!(
+            SnapshotFormat::from_path(Path::new("schema.json")),
+            SnapshotFormat::Json
+        );
+    }
+
+    #[test]
+    fn test_round_trip() {
+        for format in [SnapshotFormat::Json, SnapshotFormat::Yaml] {
+            let snapshot = Snapshot::new(sample_schema());
+            let contents = snapshot.to_string(format).unwrap();
+            let loaded = Snapshot::from_str(&contents, format).unwrap();
+
+            assert_eq!(loaded.version, SNAPSHOT_VERSION);
+            assert_eq!(loaded.schema.tables[0].name, "users");
+            assert_eq!(
+                loaded.schema.tables[0].columns[1].data_type,
+                DataType::Array(Box::new(DataType::Varchar(Some(32))))
+            );
+            assert_eq!(loaded.schema.enums[0].values, vec!["admin", "member"]);
+        }
+    }
+
+    #[test]
+    fn test_rejects_unknown_version() {
+        let contents =
+            r#"{"version": 999, "schema": {"name": "public", "tables": [], "enums": []}}"#;
+        let err = Snapshot::from_str(contents, SnapshotFormat::Json).unwrap_err();
+        assert!(err.contains("999"));
+    }
+}